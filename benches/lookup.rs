@@ -0,0 +1,41 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ruly::{
+    lookup::{lookup, Dataset},
+    property::{prop, Property},
+    table::Table,
+    variant::Variant,
+};
+
+static PROVIDER: Property<String> = prop("provider");
+static SPECIALTY: Property<String> = prop("specialty");
+
+fn registry(size: usize) -> Dataset {
+    let mut dataset = Dataset::new(["number", "specialty"]);
+    for i in 0..size {
+        dataset
+            .push(vec![
+                Some(Variant::String(format!("{i:07}A"))),
+                Some(Variant::String(format!("specialty {}", i % 50))),
+            ])
+            .unwrap();
+    }
+    dataset
+}
+
+/// Look up a provider in a registry of a million rows.
+fn provider_lookup(c: &mut Criterion) {
+    let rules = lookup(registry(1_000_000))
+        .key("number", &PROVIDER)
+        .join("specialty", &SPECIALTY)
+        .build()
+        .unwrap();
+    let mut table = Table::new();
+    table.join_entry(PROVIDER.name.clone(), "0765432A".to_string().into());
+
+    c.bench_function("provider_lookup", |b| {
+        b.iter(|| black_box(rules[0].fire(&table)))
+    });
+}
+
+criterion_group!(benches, provider_lookup);
+criterion_main!(benches);
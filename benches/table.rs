@@ -0,0 +1,80 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ruly::{
+    table::{Ident, Table},
+    variant::Variant,
+};
+use std::{collections::HashMap, rc::Rc};
+
+// The representation `Table` used before it became persistent.
+type Baseline = HashMap<Ident, Variant>;
+
+fn entries(size: u64) -> impl Iterator<Item = (Ident, Variant)> {
+    (0..size).map(|i| (Ident::Anonymous(i), Variant::Int(i as i64)))
+}
+
+fn persistent(size: u64) -> Table {
+    let mut table = Table::new();
+    for (name, value) in entries(size) {
+        table.join_entry(name, value);
+    }
+    table
+}
+
+fn baseline(size: u64) -> Baseline {
+    entries(size).collect()
+}
+
+/// Fork a table and add one entry to the fork, as in a what-if analysis.
+fn fork_and_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fork_and_update");
+    for size in [100, 10_000, 100_000] {
+        let table = persistent(size);
+        group.bench_with_input(BenchmarkId::new("persistent", size), &table, |b, t| {
+            b.iter(|| {
+                let mut fork = t.fork();
+                fork.join_entry(Ident::Anonymous(size), Variant::Int(0));
+                black_box(fork)
+            })
+        });
+
+        let table = baseline(size);
+        group.bench_with_input(BenchmarkId::new("baseline", size), &table, |b, t| {
+            b.iter(|| {
+                let mut fork = t.clone();
+                fork.insert(Ident::Anonymous(size), Variant::Int(0));
+                black_box(fork)
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Update a nested table that is shared with another variant, which forces `Rc::make_mut` to copy it.
+fn shared_nested_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("shared_nested_update");
+    for size in [100, 10_000, 100_000] {
+        let nested = Variant::Table(Rc::new(persistent(size)));
+        group.bench_with_input(BenchmarkId::new("persistent", size), &nested, |b, v| {
+            b.iter(|| {
+                let mut fork = v.clone();
+                if let Variant::Table(t) = &mut fork {
+                    Rc::make_mut(t).join_entry(Ident::Anonymous(size), Variant::Int(0));
+                }
+                black_box(fork)
+            })
+        });
+
+        let nested = Rc::new(baseline(size));
+        group.bench_with_input(BenchmarkId::new("baseline", size), &nested, |b, v| {
+            b.iter(|| {
+                let mut fork = v.clone();
+                Rc::make_mut(&mut fork).insert(Ident::Anonymous(size), Variant::Int(0));
+                black_box(fork)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, fork_and_update, shared_nested_update);
+criterion_main!(benches);
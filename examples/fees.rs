@@ -1,22 +1,36 @@
 use ruly::{
     propagator::Propagators,
     property::{prop, Property},
-    quantity::{date::Date, money::AUD, Value},
+    quantity::{
+        date::Date,
+        money::AUD,
+        proportion::{Percent, Rate},
+        Value,
+    },
     rule::infer,
+    schema::{describe, Schema},
 };
 
 fn main() {
-    fees();
+    let rules = fees();
+    if let Err(errors) = schema().check_rules(&rules) {
+        for error in errors {
+            eprintln!("{error}");
+        }
+    }
 }
 
 static _PATIENT: Property<String> = prop("patient");
 static _SERVICE_DATE: Property<Value<Date>> = prop("service_date");
 static ASSIST_51300: Property<Value<AUD>> = prop("assist_51300");
-static ASSIST_51303: Property<f64> = prop("assist_51303");
+static ASSIST_51303: Property<Value<Rate>> = prop("assist_51303");
 static ITEM: Property<u32> = prop("item");
 static SURGEON_MBS_FEE: Property<Value<AUD>> = prop("surgeon_mbs_fee");
 static ASSIST_NOGAP_FEE: Property<Value<AUD>> = prop("assist_nogap_fee");
 
+/// The assistant's share of the surgeon's fee for item 51303.
+const ASSISTANT_SHARE: Value<Percent> = Value::from_repr(0.2);
+
 #[rustfmt::skip]
 fn fees() -> Propagators {
     [
@@ -28,8 +42,17 @@ fn fees() -> Propagators {
             
         infer(&ASSIST_NOGAP_FEE) .from(&ITEM) .from(&ASSIST_51303) .from(&SURGEON_MBS_FEE)
             .rule(|input| match input {
-                (51303, r, s) => Some(s.scale(r * 0.2)),
+                (51303, r, s) => Some(s * (r * ASSISTANT_SHARE)),
                 _ => None,
             }),
     ].into()
 }
+
+fn schema() -> Schema {
+    Schema::new()
+        .declare(describe(&ITEM, "MBS item number").allow([51300, 51303]))
+        .declare(describe(&SURGEON_MBS_FEE, "The surgeon's MBS fee"))
+        .declare(&ASSIST_51300)
+        .declare(&ASSIST_51303)
+        .declare(&ASSIST_NOGAP_FEE)
+}
@@ -0,0 +1,242 @@
+use crate::variant::{Error, Variant};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    any::Any,
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    rc::Rc,
+    sync::{Mutex, OnceLock},
+};
+
+/// A user-defined value type that can be held in a `Variant::Custom`, such as an ABN or a geo point.
+///
+/// A custom type supplies its serde tag, which must be unique, and optionally its join.
+/// Its equality, order, hash and display are those of the type.
+/// Call `custom_variant!` to convert the type to and from `Variant` so that it can be the type
+/// of a `Property` in a rule, and `register` it so that it can be deserialized.
+///
+/// For example:
+///
+/// ```
+/// use ruly::{custom::{register, Custom}, custom_variant, variant::Variant};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+/// struct Abn(u64);
+///
+/// impl std::fmt::Display for Abn {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "ABN {}", self.0)
+///     }
+/// }
+///
+/// impl Custom for Abn {
+///     const TAG: &'static str = "abn";
+/// }
+///
+/// custom_variant!(Abn);
+/// register::<Abn>();
+///
+/// let v: Variant = Abn(51824753556).into();
+/// let json = serde_json::to_string(&v).unwrap();
+/// let w: Variant = serde_json::from_str(&json).unwrap();
+/// assert_eq!(Abn::try_from(w).unwrap(), Abn(51824753556));
+/// ```
+pub trait Custom:
+    Clone + Debug + Display + Eq + Ord + Hash + Serialize + DeserializeOwned + 'static
+{
+    /// The tag identifying the type in serialized values.
+    const TAG: &'static str;
+
+    /// Join two values, or `None` if they conflict.  By default only equal values join.
+    fn join(&self, other: &Self) -> Option<Self> {
+        (self == other).then(|| self.clone())
+    }
+}
+
+/// The object-safe form of `Custom`, implemented for every `Custom` type.
+pub trait CustomValue: Debug + Display {
+    fn tag(&self) -> &'static str;
+    fn as_any(&self) -> &dyn Any;
+    fn join_custom(&self, other: &dyn CustomValue) -> Option<Rc<dyn CustomValue>>;
+    fn cmp_custom(&self, other: &dyn CustomValue) -> Ordering;
+    fn hash_custom(&self, state: &mut dyn Hasher);
+    fn to_json(&self) -> Result<serde_json::Value, Error>;
+}
+
+impl<T: Custom> CustomValue for T {
+    fn tag(&self) -> &'static str {
+        T::TAG
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn join_custom(&self, other: &dyn CustomValue) -> Option<Rc<dyn CustomValue>> {
+        let other = other.as_any().downcast_ref::<T>()?;
+        Some(Rc::new(self.join(other)?))
+    }
+
+    /// Values of different types are ordered by tag.
+    fn cmp_custom(&self, other: &dyn CustomValue) -> Ordering {
+        match other.as_any().downcast_ref::<T>() {
+            Some(other) => self.cmp(other),
+            None => T::TAG.cmp(other.tag()),
+        }
+    }
+
+    fn hash_custom(&self, mut state: &mut dyn Hasher) {
+        T::TAG.hash(&mut state);
+        self.hash(&mut state);
+    }
+
+    fn to_json(&self) -> Result<serde_json::Value, Error> {
+        serde_json::to_value(self).map_err(|e| Error::Detail(e.to_string()))
+    }
+}
+
+type Reader = fn(serde_json::Value) -> Result<Rc<dyn CustomValue>, Error>;
+
+fn registry() -> &'static Mutex<HashMap<&'static str, Reader>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<&'static str, Reader>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Register a custom type so that values with its tag are deserialized to it.
+pub fn register<T: Custom>() {
+    let reader: Reader = |json| {
+        let value: T = serde_json::from_value(json).map_err(|e| Error::Detail(e.to_string()))?;
+        Ok(Rc::new(value))
+    };
+    if let Ok(mut registry) = registry().lock() {
+        registry.insert(T::TAG, reader);
+    }
+}
+
+/// Read a custom value of a registered type from its tag and serialized value.
+pub fn from_json(tag: &str, json: serde_json::Value) -> Result<Rc<dyn CustomValue>, Error> {
+    let reader = registry()
+        .lock()
+        .ok()
+        .and_then(|registry| registry.get(tag).copied())
+        .ok_or_else(|| Error::Detail(format!("unregistered custom type {tag}")))?;
+    reader(json)
+}
+
+/// Extract a custom value of type `T` from a variant.
+pub fn downcast<T: Custom>(value: &Variant) -> Option<&T> {
+    match value {
+        Variant::Custom(custom) => custom.as_any().downcast_ref(),
+        _ => None,
+    }
+}
+
+/// Implement the conversions between a `Custom` type and `Variant`, so that the type
+/// can be the type of a `Property` in a rule.
+#[macro_export]
+macro_rules! custom_variant {
+    ($t:ty) => {
+        impl From<$t> for $crate::variant::Variant {
+            fn from(value: $t) -> Self {
+                $crate::variant::Variant::Custom(::std::rc::Rc::new(value))
+            }
+        }
+
+        impl TryFrom<$crate::variant::Variant> for $t {
+            type Error = $crate::variant::Error;
+
+            fn try_from(value: $crate::variant::Variant) -> Result<Self, Self::Error> {
+                $crate::custom::downcast::<$t>(&value)
+                    .cloned()
+                    .ok_or_else(|| "incorrect type stored in variant".into())
+            }
+        }
+    };
+}
+
+/// The serialized form of a custom value: its tag and its value.
+#[derive(Serialize, Deserialize)]
+struct Tagged {
+    tag: String,
+    value: serde_json::Value,
+}
+
+pub(crate) fn serialize<S: Serializer>(
+    value: &Rc<dyn CustomValue>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let tagged = Tagged {
+        tag: value.tag().to_string(),
+        value: value.to_json().map_err(serde::ser::Error::custom)?,
+    };
+    tagged.serialize(serializer)
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Rc<dyn CustomValue>, D::Error> {
+    let tagged = Tagged::deserialize(deserializer)?;
+    from_json(&tagged.tag, tagged.value).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        json::{from_json, to_json},
+        propagator::{evaluate_naive, Propagators},
+        property::{prop, Path, Property},
+        rule::infer,
+        table::Table,
+        variant::Lattice,
+    };
+
+    /// A version number whose join is the later version.
+    #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+    struct Version(u32, u32);
+
+    impl Display for Version {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "v{}.{}", self.0, self.1)
+        }
+    }
+
+    impl Custom for Version {
+        const TAG: &'static str = "version";
+
+        fn join(&self, other: &Self) -> Option<Self> {
+            Some(self.max(other).clone())
+        }
+    }
+
+    custom_variant!(Version);
+
+    #[test]
+    fn custom_values() {
+        static SCHEDULE: Property<Version> = prop("schedule");
+        static MAJOR: Property<u32> = prop("major");
+
+        let rules: Propagators = vec![infer(&MAJOR).from(&SCHEDULE).rule(|v: Version| Some(v.0))];
+        let mut table = Table::new();
+        table.join_entry(SCHEDULE.name.clone(), Version(2, 1).into());
+        assert!(table.join_entry(SCHEDULE.name.clone(), Version(2, 3).into()));
+        assert!(!table.join_entry(SCHEDULE.name.clone(), Version(1, 9).into()));
+        evaluate_naive(&mut table, &rules, 5).unwrap();
+        assert_eq!(Path::from(&MAJOR).query(&table), Some(2));
+        assert_eq!(Path::from(&SCHEDULE).query(&table), Some(Version(2, 3)));
+
+        let v: Variant = Version(2, 3).into();
+        assert_eq!(v.to_string(), "v2.3");
+        assert_eq!(v, Version(2, 3).into());
+        assert!(v.clone().join(Variant::Int(2)) > v);
+
+        register::<Version>();
+        let json = to_json(&v, None);
+        assert_eq!(from_json(&json, None).unwrap(), v);
+        let text = serde_json::to_string(&v).unwrap();
+        assert_eq!(serde_json::from_str::<Variant>(&text).unwrap(), v);
+    }
+}
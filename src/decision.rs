@@ -0,0 +1,689 @@
+use crate::{
+    propagator::{Propagator, Propagators},
+    table::{Element, Ident, IdentPath, Set, Table},
+    variant::{Error, Variant},
+};
+use chrono::NaiveDate;
+use std::{cmp::Ordering, rc::Rc, str::FromStr};
+
+#[cfg(feature = "quantity")]
+use crate::quantity::{currency::CurrencyInfo, money::money_from_str};
+
+/// A decision table maps conditions on input properties to values of output properties.
+///
+/// Each row has a condition cell for each input and a value cell for each output.
+/// A row matches when all its conditions hold and the `HitPolicy` decides which matching
+/// rows give the outputs.  The table is converted to a `Propagator` for each output by `build`.
+///
+/// A table can be read from CSV by `from_csv`. The header names the columns,
+/// with outputs marked by a leading `=`. A column may be typed with a suffix such as `:int`
+/// and an output may list its values in priority order in braces, which the
+/// `Priority` policy requires. For example:
+///
+/// ```text
+/// item,provider,in_hospital,=fee:AUD
+/// 51300,-,-,$310.35
+/// "51303, 51305",GP,>=1,$95.00
+/// ```
+///
+/// A condition cell is written in a form of FEEL unary tests:
+/// `-` or blank for any value, a literal for equality, a comparison such as `>=3`,
+/// a range such as `[1..5)`, a comma separated list of these, or `not(...)` of a list.
+/// String literals may be quoted.
+#[derive(Debug, Clone)]
+pub struct DecisionTable {
+    policy: HitPolicy,
+    inputs: Vec<Column>,
+    outputs: Vec<Column>,
+    rows: Vec<Row>,
+}
+
+/// Determines the outputs when several rows of a `DecisionTable` match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitPolicy {
+    /// At most one row may match, otherwise the outputs are `Invalid`.
+    Unique,
+    /// The first matching row in table order.
+    First,
+    /// The matching row whose outputs come first in the output value lists.
+    Priority,
+    /// All matching rows, aggregated.
+    Collect(Aggregate),
+}
+
+/// The aggregation for the `Collect` hit policy.
+///
+/// When no row matches, `Sum`, `Min` and `Max` give no value,
+/// `Count` gives 0 and `List` gives the empty set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// The set of output values.
+    List,
+    /// The sum of numeric output values, which is `Invalid` if an integer sum overflows
+    /// or an output is not a number.
+    Sum,
+    Min,
+    Max,
+    Count,
+}
+
+/// The type of the values in a column, which determines how cells are read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellType {
+    /// An integer, float or ISO date if the cell can be read as one, otherwise a string.
+    Infer,
+    Int,
+    Float,
+    String,
+    Date,
+    /// An amount of money represented as `Int` minor units.
+    #[cfg(feature = "quantity")]
+    Money(&'static CurrencyInfo),
+}
+
+/// An input or output column of a `DecisionTable`.
+#[derive(Debug, Clone)]
+pub struct Column {
+    pub path: IdentPath,
+    pub cell_type: CellType,
+    /// The allowed output values in priority order, if given.
+    pub values: Vec<Variant>,
+}
+
+#[derive(Debug, Clone)]
+struct Row {
+    conditions: Vec<Condition>,
+    outputs: Vec<Option<Variant>>,
+}
+
+/// A test applied to an input value in a `DecisionTable`.
+#[derive(Debug, Clone)]
+pub enum Condition {
+    Any,
+    Compare(Ordering, bool, Variant),
+    Range(Variant, bool, Variant, bool),
+    OneOf(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+impl FromStr for HitPolicy {
+    type Err = Error;
+
+    /// Read a hit policy by name or by its DMN abbreviation e.g. `U`, `C+` or `COLLECT SUM`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Aggregate::*;
+        use HitPolicy::*;
+        Ok(match s.trim().to_uppercase().as_str() {
+            "U" | "UNIQUE" => Unique,
+            "F" | "FIRST" => First,
+            "P" | "PRIORITY" => Priority,
+            "C" | "COLLECT" => Collect(List),
+            "C+" | "COLLECT SUM" => Collect(Sum),
+            "C<" | "COLLECT MIN" => Collect(Min),
+            "C>" | "COLLECT MAX" => Collect(Max),
+            "C#" | "COLLECT COUNT" => Collect(Count),
+            _ => Err(Error::Detail(format!("unknown hit policy '{s}'")))?,
+        })
+    }
+}
+
+impl FromStr for CellType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.trim().to_lowercase().as_str() {
+            "" => CellType::Infer,
+            "int" => CellType::Int,
+            "float" => CellType::Float,
+            "string" => CellType::String,
+            "date" => CellType::Date,
+            #[cfg(feature = "quantity")]
+            code if crate::quantity::currency::lookup(code).is_some() => {
+                CellType::Money(crate::quantity::currency::lookup(code).unwrap())
+            }
+            _ => Err(Error::Detail(format!("unknown column type '{s}'")))?,
+        })
+    }
+}
+
+impl CellType {
+    /// Read a literal value.  A quoted literal is always a string.
+    pub fn parse(&self, text: &str) -> Result<Variant, Error> {
+        let text = text.trim();
+        if let Some(quoted) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+            return Ok(Variant::String(quoted.to_string()));
+        }
+        let error = || Error::Detail(format!("expected {self:?} value, got '{text}'"));
+        Ok(match self {
+            CellType::Infer => text
+                .parse::<i64>()
+                .map(Variant::Int)
+                .or_else(|_| text.parse::<f64>().map(Variant::Float))
+                .or_else(|_| NaiveDate::parse_from_str(text, "%F").map(Variant::Date))
+                .unwrap_or_else(|_| Variant::String(text.to_string())),
+            CellType::Int => Variant::Int(text.parse().or(Err(error()))?),
+            CellType::Float => Variant::Float(text.parse().or(Err(error()))?),
+            CellType::String => Variant::String(text.to_string()),
+            CellType::Date => {
+                Variant::Date(NaiveDate::parse_from_str(text, "%F").or(Err(error()))?)
+            }
+            #[cfg(feature = "quantity")]
+            CellType::Money(currency) => Variant::Int(money_from_str(text, currency, None)?),
+        })
+    }
+}
+
+impl FromStr for Column {
+    type Err = Error;
+
+    /// Read a column header of the form `path:type{value|value}` omitting the `=` output mark.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (head, values) = match s.trim().split_once('{') {
+            Some((head, rest)) => {
+                let list = rest
+                    .strip_suffix('}')
+                    .ok_or("expected '}' in column header")?;
+                (head, Some(list))
+            }
+            None => (s.trim(), None),
+        };
+        let (name, cell_type) = head.split_once(':').unwrap_or((head, ""));
+        let cell_type: CellType = cell_type.parse()?;
+        let mut names = name.trim().split('/').map(|n| {
+            if n.trim().is_empty() {
+                Err(Error::Detail(format!("empty name in column header '{s}'")))
+            } else {
+                Ok(Ident::NonIntern(n.trim().to_string()))
+            }
+        });
+        let mut path = IdentPath::new(names.next().unwrap()?);
+        for name in names {
+            path = path.append(name?);
+        }
+        let values = values
+            .into_iter()
+            .flat_map(|list| list.split('|'))
+            .map(|v| cell_type.parse(v))
+            .collect::<Result<_, _>>()?;
+        Ok(Column {
+            path,
+            cell_type,
+            values,
+        })
+    }
+}
+
+impl Condition {
+    /// Read a condition cell with literals of the given type.
+    pub fn parse(text: &str, cell_type: &CellType) -> Result<Self, Error> {
+        let text = text.trim();
+        if text.is_empty() || text == "-" {
+            return Ok(Condition::Any);
+        }
+        if let Some(inner) = text.strip_prefix("not(").and_then(|t| t.strip_suffix(')')) {
+            return Ok(Condition::Not(Box::new(Self::parse(inner, cell_type)?)));
+        }
+        let tests = split_list(text)
+            .into_iter()
+            .map(|t| Self::parse_test(t.trim(), cell_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(if tests.len() == 1 {
+            tests.into_iter().next().unwrap()
+        } else {
+            Condition::OneOf(tests)
+        })
+    }
+
+    fn parse_test(text: &str, cell_type: &CellType) -> Result<Self, Error> {
+        use Ordering::*;
+        for (op, ordering, inclusive) in [
+            ("<=", Less, true),
+            (">=", Greater, true),
+            ("<", Less, false),
+            (">", Greater, false),
+            ("=", Equal, true),
+        ] {
+            if let Some(rest) = text.strip_prefix(op) {
+                return Ok(Condition::Compare(
+                    ordering,
+                    inclusive,
+                    cell_type.parse(rest)?,
+                ));
+            }
+        }
+        let open = text.starts_with(['[', '(', ']']);
+        if let (true, Some((lo, hi))) = (open, text.split_once("..")) {
+            let lo_inclusive = lo.starts_with('[');
+            let hi_inclusive = hi.ends_with(']');
+            if !hi.ends_with([']', ')', '[']) {
+                Err(Error::Detail(format!("expected end of range in '{text}'")))?
+            }
+            return Ok(Condition::Range(
+                cell_type.parse(&lo[1..])?,
+                lo_inclusive,
+                cell_type.parse(&hi[..hi.len() - 1])?,
+                hi_inclusive,
+            ));
+        }
+        Ok(Condition::Compare(
+            Ordering::Equal,
+            true,
+            cell_type.parse(text)?,
+        ))
+    }
+
+    /// Test a value.
+    pub fn test(&self, value: &Variant) -> bool {
+        match self {
+            Condition::Any => true,
+            Condition::Compare(ordering, inclusive, literal) => {
+                compare(value, literal).is_some_and(|o| o == *ordering || (*inclusive && o.is_eq()))
+            }
+            Condition::Range(lo, lo_inclusive, hi, hi_inclusive) => {
+                let above =
+                    compare(value, lo).is_some_and(|o| o.is_gt() || (*lo_inclusive && o.is_eq()));
+                let below =
+                    compare(value, hi).is_some_and(|o| o.is_lt() || (*hi_inclusive && o.is_eq()));
+                above && below
+            }
+            Condition::OneOf(tests) => tests.iter().any(|t| t.test(value)),
+            Condition::Not(test) => !test.test(value),
+        }
+    }
+}
+
+// Split a list at commas that are not in quotes or brackets.
+pub(crate) fn split_list(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut depth, mut quoted) = (0, 0, false);
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '[' | '(' if !quoted => depth += 1,
+            ']' | ')' if !quoted && depth > 0 => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// Compare scalar values of like type, treating `Int` and `Float` as numbers.
+fn compare(a: &Variant, b: &Variant) -> Option<Ordering> {
+    use Variant::*;
+    match (a, b) {
+        (Int(a), Int(b)) => Some(a.cmp(b)),
+        (Float(a), Float(b)) => a.partial_cmp(b),
+        (Int(a), Float(b)) => (*a as f64).partial_cmp(b),
+        (Float(a), Int(b)) => a.partial_cmp(&(*b as f64)),
+        (String(a), String(b)) => Some(a.cmp(b)),
+        (Date(a), Date(b)) => Some(a.cmp(b)),
+        (Instant(a), Instant(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+impl DecisionTable {
+    /// An empty table with the given columns.
+    pub fn new(policy: HitPolicy, inputs: Vec<Column>, outputs: Vec<Column>) -> Self {
+        Self {
+            policy,
+            inputs,
+            outputs,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row given the text of its condition and output cells.
+    pub fn add_row<'a>(
+        &mut self,
+        conditions: impl IntoIterator<Item = &'a str>,
+        outputs: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(), Error> {
+        let conditions: Vec<&str> = conditions.into_iter().collect();
+        let outputs: Vec<&str> = outputs.into_iter().collect();
+        if conditions.len() != self.inputs.len() || outputs.len() != self.outputs.len() {
+            Err("wrong number of cells in decision table row")?
+        }
+        let conditions: Vec<_> = conditions
+            .into_iter()
+            .zip(&self.inputs)
+            .map(|(cell, column)| {
+                Condition::parse(cell, &column.cell_type)
+                    .map_err(|e| Error::Detail(format!("column {}: {e}", column.path)))
+            })
+            .collect::<Result<_, _>>()?;
+        let outputs: Vec<_> = outputs
+            .into_iter()
+            .zip(&self.outputs)
+            .map(|(cell, column)| {
+                if cell.trim().is_empty() {
+                    return Ok(None);
+                }
+                let value = column
+                    .cell_type
+                    .parse(cell)
+                    .map_err(|e| Error::Detail(format!("column {}: {e}", column.path)))?;
+                if !column.values.is_empty()
+                    && !column
+                        .values
+                        .iter()
+                        .any(|v| compare(v, &value).is_some_and(Ordering::is_eq))
+                {
+                    Err(Error::Detail(format!(
+                        "column {}: '{}' is not an allowed value",
+                        column.path,
+                        cell.trim()
+                    )))?
+                }
+                Ok(Some(value))
+            })
+            .collect::<Result<_, Error>>()?;
+        self.rows.push(Row {
+            conditions,
+            outputs,
+        });
+        Ok(())
+    }
+
+    /// Read a table from CSV with the given hit policy.
+    /// All errors in the header and rows are reported, identified by line number.
+    #[cfg(feature = "csv")]
+    pub fn from_csv(policy: HitPolicy, reader: impl std::io::Read) -> Result<Self, Vec<Error>> {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let header = reader
+            .headers()
+            .map_err(|e| vec![Error::Detail(e.to_string())])?
+            .clone();
+        let mut errors = Vec::new();
+        let (mut inputs, mut outputs, mut is_output) = (Vec::new(), Vec::new(), Vec::new());
+        for name in header.iter() {
+            let (column, output) = match name.strip_prefix('=') {
+                Some(name) => (name.parse::<Column>(), true),
+                None => (name.parse::<Column>(), false),
+            };
+            match column {
+                Ok(column) if output => outputs.push(column),
+                Ok(column) => inputs.push(column),
+                Err(e) => errors.push(Error::Detail(format!("line 1: {e}"))),
+            }
+            is_output.push(output);
+        }
+        let mut table = Self::new(policy, inputs, outputs);
+
+        for record in reader.records() {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    errors.push(Error::Detail(e.to_string()));
+                    continue;
+                }
+            };
+            let line = record.position().map(|p| p.line()).unwrap_or_default();
+            let cells = || record.iter().zip(&is_output);
+            let conditions = cells().filter(|(_, o)| !**o).map(|(c, _)| c);
+            let outputs = cells().filter(|(_, o)| **o).map(|(c, _)| c);
+            if let Err(e) = table.add_row(conditions, outputs) {
+                errors.push(Error::Detail(format!("line {line}: {e}")));
+            }
+        }
+        if let Err(e) = table.validate() {
+            errors.push(e);
+        }
+        if errors.is_empty() {
+            Ok(table)
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.outputs.is_empty() {
+            Err("decision table has no output columns")?
+        }
+        if self.policy == HitPolicy::Priority && self.outputs.iter().all(|c| c.values.is_empty()) {
+            Err("priority hit policy requires output values")?
+        }
+        Ok(())
+    }
+
+    /// Convert the table to a `Propagator` for each output.
+    pub fn build(self) -> Result<Propagators, Error> {
+        self.validate()?;
+        let outputs = self.outputs.len();
+        let table = Rc::new(self);
+        (0..outputs)
+            .map(|output| {
+                let path = &table.outputs[output].path;
+                if path.iter().count() > 1 {
+                    Err(Error::Detail(format!(
+                        "output {path} must be a name, not a path"
+                    )))?
+                }
+                Ok(Box::new(DecisionOutput {
+                    table: table.clone(),
+                    output,
+                }) as Box<dyn Propagator>)
+            })
+            .collect()
+    }
+
+    fn matches<'a>(
+        &'a self,
+        inputs: &'a [&Variant],
+    ) -> impl Iterator<Item = (usize, &'a Row)> + 'a {
+        self.rows.iter().enumerate().filter(move |(_, row)| {
+            row.conditions
+                .iter()
+                .zip(inputs)
+                .all(|(condition, value)| condition.test(value))
+        })
+    }
+
+    // The position of a row's outputs in the output value lists.
+    fn priority(&self, row: &Row) -> Vec<usize> {
+        self.outputs
+            .iter()
+            .zip(&row.outputs)
+            .filter(|(column, _)| !column.values.is_empty())
+            .map(|(column, value)| {
+                value
+                    .as_ref()
+                    .and_then(|value| {
+                        column
+                            .values
+                            .iter()
+                            .position(|v| compare(v, value).is_some_and(Ordering::is_eq))
+                    })
+                    .unwrap_or(usize::MAX)
+            })
+            .collect()
+    }
+
+    fn evaluate(&self, inputs: &[&Variant], output: usize) -> Option<Variant> {
+        let mut matches = self.matches(inputs);
+        match self.policy {
+            HitPolicy::First => matches.next()?.1.outputs[output].clone(),
+            HitPolicy::Unique => {
+                let (first, row) = matches.next()?;
+                match matches.next() {
+                    None => row.outputs[output].clone(),
+                    Some((second, _)) => Some(Variant::Invalid(Error::Detail(format!(
+                        "decision table rows {} and {} both match",
+                        first + 1,
+                        second + 1
+                    )))),
+                }
+            }
+            HitPolicy::Priority => {
+                matches.min_by_key(|(_, row)| self.priority(row))?.1.outputs[output].clone()
+            }
+            HitPolicy::Collect(aggregate) => {
+                let values = matches.filter_map(|(_, row)| row.outputs[output].clone());
+                collect(aggregate, values)
+            }
+        }
+    }
+}
+
+fn collect(aggregate: Aggregate, mut values: impl Iterator<Item = Variant>) -> Option<Variant> {
+    let extreme = |values: &mut dyn Iterator<Item = Variant>, wanted: Ordering| {
+        values.reduce(|a, b| {
+            if compare(&b, &a) == Some(wanted) {
+                b
+            } else {
+                a
+            }
+        })
+    };
+    match aggregate {
+        Aggregate::Count => Some(Variant::Int(values.count() as i64)),
+        Aggregate::List => Some(Variant::Set(Set::new(values.map(|v| {
+            Element::try_from(v.clone()).unwrap_or_else(|_| v.to_string().into())
+        })))),
+        Aggregate::Min => extreme(&mut values, Ordering::Less),
+        Aggregate::Max => extreme(&mut values, Ordering::Greater),
+        Aggregate::Sum => values.reduce(|total, value| match (total, value) {
+            (invalid @ Variant::Invalid(_), _) => invalid,
+            (Variant::Int(a), Variant::Int(b)) => match a.checked_add(b) {
+                Some(sum) => Variant::Int(sum),
+                None => Variant::Invalid("overflow in sum of decision table outputs".into()),
+            },
+            (Variant::Int(a), Variant::Float(b)) => Variant::Float(a as f64 + b),
+            (Variant::Float(a), Variant::Int(b)) => Variant::Float(a + b as f64),
+            (Variant::Float(a), Variant::Float(b)) => Variant::Float(a + b),
+            _ => Variant::Invalid("cannot sum non-numeric decision table outputs".into()),
+        }),
+    }
+}
+
+/// A `Propagator` for one output of a `DecisionTable`.
+struct DecisionOutput {
+    table: Rc<DecisionTable>,
+    output: usize,
+}
+
+impl Propagator for DecisionOutput {
+    fn target(&self) -> &Ident {
+        self.table.outputs[self.output].path.subject()
+    }
+
+    fn dependencies(&self) -> Vec<&IdentPath> {
+        self.table.inputs.iter().map(|c| &c.path).collect()
+    }
+
+    fn fire(&self, state: &Table) -> Option<Variant> {
+        let inputs = self
+            .table
+            .inputs
+            .iter()
+            .map(|c| state.get_path(&c.path))
+            .collect::<Option<Vec<_>>>()?;
+        self.table.evaluate(&inputs, self.output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::propagator::evaluate_naive;
+
+    #[test]
+    fn conditions() {
+        let parse = |text| Condition::parse(text, &CellType::Infer).unwrap();
+        assert!(parse("-").test(&Variant::Int(7)));
+        assert!(parse("51300").test(&Variant::Int(51300)));
+        assert!(parse(">=3").test(&Variant::Float(3.0)));
+        assert!(!parse(">3").test(&Variant::Int(3)));
+        assert!(parse("[1..5)").test(&Variant::Int(1)));
+        assert!(!parse("[1..5)").test(&Variant::Int(5)));
+        assert!(parse("51303, 51305").test(&Variant::Int(51305)));
+        assert!(parse("\"GP\", specialist").test(&Variant::String("specialist".into())));
+        assert!(parse("not(1, 2)").test(&Variant::Int(3)));
+        assert!(!parse("GP").test(&Variant::Int(3)));
+        assert!(parse("<2024-07-01").test(&Variant::Date(
+            NaiveDate::from_ymd_opt(2024, 6, 30).unwrap()
+        )));
+        assert!(Condition::parse(">=x", &CellType::Int).is_err());
+        assert!("C+".parse::<HitPolicy>().unwrap() == HitPolicy::Collect(Aggregate::Sum));
+    }
+
+    #[cfg(all(feature = "csv", feature = "quantity"))]
+    #[test]
+    fn csv_decision_tables() {
+        let schedule = "\
+item:int,provider,in_hospital,=fee:AUD,=category{high|medium|low}
+51300,-,-,$310.35,low
+\"51303, 51305\",GP,>=1,$95.00,medium
+\"51303, 51305\",-,-,$90.00,high
+";
+        let run = |policy, input: &[(&'static str, Variant)]| {
+            let rules = DecisionTable::from_csv(policy, schedule.as_bytes())
+                .unwrap()
+                .build()
+                .unwrap();
+            let mut table = Table::new();
+            for (name, value) in input {
+                table.join_entry(Ident::Intern(name), value.clone());
+            }
+            evaluate_naive(&mut table, &rules, 5).unwrap();
+            (
+                table.get(&Ident::Intern("fee")).cloned(),
+                table.get(&Ident::Intern("category")).cloned(),
+            )
+        };
+        let gp = [
+            ("item", Variant::Int(51303)),
+            ("provider", Variant::String("GP".into())),
+            ("in_hospital", Variant::Int(1)),
+        ];
+        assert!(
+            matches!(run(HitPolicy::First, &gp), (Some(Variant::Int(9500)), Some(Variant::String(c))) if c == "medium")
+        );
+        assert!(matches!(
+            run(HitPolicy::Priority, &gp).0,
+            Some(Variant::Int(9000))
+        ));
+        assert!(matches!(
+            run(HitPolicy::Unique, &gp).0,
+            Some(Variant::Invalid(_))
+        ));
+        assert!(matches!(
+            run(HitPolicy::Collect(Aggregate::Sum), &gp).0,
+            Some(Variant::Int(18500))
+        ));
+        assert!(matches!(
+            run(HitPolicy::Collect(Aggregate::Count), &gp).0,
+            Some(Variant::Int(2))
+        ));
+        assert!(matches!(
+            run(HitPolicy::Collect(Aggregate::Max), &gp).0,
+            Some(Variant::Int(9500))
+        ));
+
+        assert!(collect(Aggregate::Sum, std::iter::empty()).is_none());
+        assert!(matches!(
+            collect(Aggregate::Count, std::iter::empty()),
+            Some(Variant::Int(0))
+        ));
+        let overflow = [Variant::Int(i64::MAX), Variant::Int(1), Variant::Int(-1)];
+        assert!(matches!(
+            collect(Aggregate::Sum, overflow.into_iter()),
+            Some(Variant::Invalid(_))
+        ));
+
+        let errors = DecisionTable::from_csv(
+            HitPolicy::Unique,
+            "item:int,=fee:AUD\n51300,$1.00\nabc,$2.00\n51303,$x\n".as_bytes(),
+        )
+        .unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].to_string().starts_with("line 3"));
+    }
+}
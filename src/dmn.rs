@@ -0,0 +1,409 @@
+use crate::{
+    decision::{split_list, Aggregate, CellType, Column, DecisionTable, HitPolicy},
+    propagator::Propagators,
+    table::{Ident, IdentPath},
+    variant::Error,
+};
+use roxmltree::{Document, Node};
+
+/// Import the decision tables of a DMN 1.3 model and convert them to propagators.
+///
+/// See `decision_tables` for the supported subset of DMN.
+pub fn import(xml: &str) -> Result<Propagators, Vec<Error>> {
+    let mut propagators = Propagators::new();
+    let mut errors = Vec::new();
+    for table in decision_tables(xml)? {
+        match table.build() {
+            Ok(built) => propagators.extend(built),
+            Err(e) => errors.push(e),
+        }
+    }
+    if errors.is_empty() {
+        Ok(propagators)
+    } else {
+        Err(errors)
+    }
+}
+
+/// Read the decision tables of a DMN 1.3 model.
+///
+/// The input expression of an input column is taken to be a property name,
+/// with `.` separating the names of nested tables. An output column is named by its `name`.
+/// The `typeRef` of a column may be `number`, `string`, `date` or a currency code such as `AUD`.
+/// Input entries are FEEL unary tests limited to those of a `decision::DecisionTable` with literals
+/// that are numbers, quoted strings or `date("...")`.  Output entries are such literals.
+/// The hit policies `UNIQUE`, `FIRST`, `PRIORITY` and `COLLECT` are supported.
+///
+/// Anything else in a decision, such as a literal expression, a boolean type or other FEEL,
+/// is reported as an error rather than ignored.  All errors are reported together.
+pub fn decision_tables(xml: &str) -> Result<Vec<DecisionTable>, Vec<Error>> {
+    let document = Document::parse(xml).map_err(|e| vec![Error::Detail(e.to_string())])?;
+    let root = document.root_element();
+    if root.tag_name().name() != "definitions" {
+        return Err(vec!["expected DMN definitions".into()]);
+    }
+    let mut tables = Vec::new();
+    let mut errors = Vec::new();
+    for decision in elements(root, "decision") {
+        let name = decision
+            .attribute("name")
+            .or(decision.attribute("id"))
+            .unwrap_or_default();
+        let logic: Vec<_> = decision
+            .children()
+            .filter(|n| n.is_element() && !is_description(n))
+            .collect();
+        match &logic[..] {
+            [table] if table.tag_name().name() == "decisionTable" => match decision_table(table) {
+                Ok(table) => tables.push(table),
+                Err(found) => errors.extend(
+                    found
+                        .into_iter()
+                        .map(|e| Error::Detail(format!("decision '{name}': {e}"))),
+                ),
+            },
+            _ => errors.push(Error::Detail(format!(
+                "decision '{name}': only a decision table is supported, found {}",
+                names(&logic)
+            ))),
+        }
+    }
+    if errors.is_empty() {
+        Ok(tables)
+    } else {
+        Err(errors)
+    }
+}
+
+// Elements that document a model and do not affect its meaning.
+fn is_description(node: &Node) -> bool {
+    matches!(
+        node.tag_name().name(),
+        "description"
+            | "extensionElements"
+            | "variable"
+            | "informationRequirement"
+            | "authorityRequirement"
+            | "knowledgeRequirement"
+            | "annotation"
+    )
+}
+
+fn elements<'a, 'i>(node: Node<'a, 'i>, name: &'static str) -> impl Iterator<Item = Node<'a, 'i>> {
+    node.children()
+        .filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn names(nodes: &[Node]) -> String {
+    let names: Vec<_> = nodes.iter().map(|n| n.tag_name().name()).collect();
+    if names.is_empty() {
+        "nothing".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+fn text<'a>(node: Node<'a, '_>) -> &'a str {
+    elements(node, "text")
+        .next()
+        .and_then(|t| t.text())
+        .unwrap_or_default()
+        .trim()
+}
+
+fn decision_table(node: &Node) -> Result<DecisionTable, Vec<Error>> {
+    let mut errors = Vec::new();
+    let policy = hit_policy(node).unwrap_or_else(|e| {
+        errors.push(e);
+        HitPolicy::First
+    });
+    let (mut inputs, mut outputs) = (Vec::new(), Vec::new());
+
+    for child in node.children().filter(|n| n.is_element()) {
+        let result = match child.tag_name().name() {
+            "input" => input_column(&child).map(|c| inputs.push(c)),
+            "output" => output_column(&child).map(|c| outputs.push(c)),
+            "rule" | "annotation" | "description" | "extensionElements" => Ok(()),
+            other => Err(Error::Detail(format!("unsupported element {other}"))),
+        };
+        if let Err(e) = result {
+            errors.push(e);
+        }
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut table = DecisionTable::new(policy, inputs.clone(), outputs.clone());
+    for (i, rule) in elements(*node, "rule").enumerate() {
+        let id = rule
+            .attribute("id")
+            .map(str::to_string)
+            .unwrap_or_else(|| (i + 1).to_string());
+        let cells = |name, columns: &[Column]| -> Result<Vec<String>, Error> {
+            let entries: Vec<_> = elements(rule, name).collect();
+            if entries.len() != columns.len() {
+                Err(Error::Detail(format!("expected {} {name}s", columns.len())))?
+            }
+            entries
+                .iter()
+                .zip(columns)
+                .map(|(entry, column)| {
+                    feel_cell(text(*entry), name == "inputEntry")
+                        .map_err(|e| Error::Detail(format!("column {}: {e}", column.path)))
+                })
+                .collect()
+        };
+        let row = cells("inputEntry", &inputs).and_then(|conditions| {
+            let results = cells("outputEntry", &outputs)?;
+            table.add_row(
+                conditions.iter().map(String::as_str),
+                results.iter().map(String::as_str),
+            )
+        });
+        if let Err(e) = row {
+            errors.push(Error::Detail(format!("rule {id}: {e}")));
+        }
+    }
+    if errors.is_empty() {
+        Ok(table)
+    } else {
+        Err(errors)
+    }
+}
+
+fn hit_policy(node: &Node) -> Result<HitPolicy, Error> {
+    let policy = node.attribute("hitPolicy").unwrap_or("UNIQUE");
+    let aggregation = node.attribute("aggregation");
+    match (policy, aggregation) {
+        ("COLLECT", Some(aggregation)) => Ok(HitPolicy::Collect(match aggregation {
+            "SUM" => Aggregate::Sum,
+            "MIN" => Aggregate::Min,
+            "MAX" => Aggregate::Max,
+            "COUNT" => Aggregate::Count,
+            other => Err(Error::Detail(format!("unsupported aggregation {other}")))?,
+        })),
+        ("UNIQUE" | "FIRST" | "PRIORITY" | "COLLECT", None) => policy.parse(),
+        _ => Err(Error::Detail(format!("unsupported hit policy {policy}"))),
+    }
+}
+
+fn cell_type(node: &Node) -> Result<CellType, Error> {
+    match node.attribute("typeRef") {
+        None | Some("number") => Ok(CellType::Infer),
+        Some("string") => Ok(CellType::String),
+        Some("date") => Ok(CellType::Date),
+        Some(other) => other
+            .parse()
+            .map_err(|_| Error::Detail(format!("unsupported typeRef {other}"))),
+    }
+}
+
+fn input_column(node: &Node) -> Result<Column, Error> {
+    let label = node
+        .attribute("label")
+        .or(node.attribute("id"))
+        .unwrap_or_default();
+    if let Some(values) = elements(*node, "inputValues").next() {
+        Err(Error::Detail(format!(
+            "input '{label}': unsupported inputValues '{}'",
+            text(values)
+        )))?
+    }
+    let expression = elements(*node, "inputExpression")
+        .next()
+        .ok_or_else(|| Error::Detail(format!("input '{label}': expected an inputExpression")))?;
+    let name = text(expression);
+    let mut names = name.split('.').map(|n| {
+        if is_name(n) {
+            Ok(Ident::NonIntern(n.to_string()))
+        } else {
+            Err(Error::Detail(format!(
+                "input '{label}': unsupported input expression '{name}'"
+            )))
+        }
+    });
+    let mut path = IdentPath::new(names.next().unwrap()?);
+    for n in names {
+        path = path.append(n?);
+    }
+    Ok(Column {
+        path,
+        cell_type: cell_type(&expression)?,
+        values: Vec::new(),
+    })
+}
+
+fn output_column(node: &Node) -> Result<Column, Error> {
+    let name = node.attribute("name").unwrap_or_default();
+    if !is_name(name) {
+        Err(Error::Detail(format!(
+            "output name '{name}' is not a property name"
+        )))?
+    }
+    let cell_type = cell_type(node)?;
+    let values = match elements(*node, "outputValues").next() {
+        Some(values) => split_list(text(values))
+            .into_iter()
+            .map(|v| cell_type.parse(&feel_cell(v, false)?))
+            .collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+    Ok(Column {
+        path: IdentPath::new(Ident::NonIntern(name.to_string())),
+        cell_type,
+        values,
+    })
+}
+
+fn is_name(text: &str) -> bool {
+    text.chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && text.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+// Translate a FEEL cell to the condition syntax of a decision table, checking it is in the supported subset.
+// An output cell must be a single literal.
+fn feel_cell(text: &str, condition: bool) -> Result<String, Error> {
+    let text = text.trim();
+    let unsupported = || Error::Detail(format!("unsupported FEEL '{text}'"));
+    if !condition {
+        return literal(text).ok_or_else(unsupported);
+    }
+    if text.is_empty() || text == "-" {
+        return Ok("-".to_string());
+    }
+    if let Some(inner) = text.strip_prefix("not(").and_then(|t| t.strip_suffix(')')) {
+        return Ok(format!("not({})", feel_cell(inner, true)?));
+    }
+    let tests = split_list(text)
+        .into_iter()
+        .map(|test| {
+            let test = test.trim();
+            for op in ["<=", ">=", "<", ">", "="] {
+                if let Some(rest) = test.strip_prefix(op) {
+                    return literal(rest).map(|l| format!("{op}{l}"));
+                }
+            }
+            let open = test.starts_with(['[', '(', ']']);
+            let close = test.ends_with([']', ')', '[']);
+            let range = (open && close)
+                .then(|| test.get(1..test.len() - 1))
+                .flatten()
+                .and_then(|t| t.split_once(".."));
+            match range {
+                Some((lo, hi)) => Some(format!(
+                    "{}{}..{}{}",
+                    &test[..1],
+                    literal(lo)?,
+                    literal(hi)?,
+                    &test[test.len() - 1..]
+                )),
+                _ => literal(test),
+            }
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(unsupported)?;
+    Ok(tests.join(","))
+}
+
+// A FEEL literal: a number, a quoted string or a date.
+fn literal(text: &str) -> Option<String> {
+    let text = text.trim();
+    if let Some(date) = text
+        .strip_prefix("date(\"")
+        .and_then(|t| t.strip_suffix("\")"))
+    {
+        return Some(date.to_string());
+    }
+    let quoted = text.len() >= 2
+        && text.starts_with('"')
+        && text.ends_with('"')
+        && !text[1..text.len() - 1].contains('"');
+    if quoted || text.parse::<f64>().is_ok_and(f64::is_finite) {
+        Some(text.to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{propagator::evaluate_naive, table::Table, variant::Variant};
+
+    static MODEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<definitions xmlns="https://www.omg.org/spec/DMN/20191111/MODEL/" id="fees" name="Fees" namespace="ruly">
+  <decision id="assist_fee" name="Assistant fee">
+    <decisionTable id="t1" hitPolicy="FIRST">
+      <input id="i1" label="Item">
+        <inputExpression id="e1" typeRef="number"><text>item</text></inputExpression>
+      </input>
+      <input id="i2" label="Provider">
+        <inputExpression id="e2" typeRef="string"><text>provider.type</text></inputExpression>
+      </input>
+      <output id="o1" name="fee" typeRef="AUD"/>
+      <rule id="r1">
+        <inputEntry><text>51300</text></inputEntry>
+        <inputEntry><text>-</text></inputEntry>
+        <outputEntry><text>310.35</text></outputEntry>
+      </rule>
+      <rule id="r2">
+        <inputEntry><text>[51303..51305]</text></inputEntry>
+        <inputEntry><text>not("GP")</text></inputEntry>
+        <outputEntry><text>95.00</text></outputEntry>
+      </rule>
+    </decisionTable>
+  </decision>
+</definitions>"#;
+
+    #[test]
+    fn dmn_import() {
+        let rules = import(MODEL).unwrap();
+        let mut provider = Table::new();
+        provider.join_entry(Ident::Intern("type"), "specialist".to_string().into());
+        let mut table = Table::new();
+        table.join_entry(Ident::Intern("item"), 51304.into());
+        table.join_entry(Ident::Intern("provider"), std::rc::Rc::new(provider).into());
+        evaluate_naive(&mut table, &rules, 5).unwrap();
+        assert!(matches!(
+            table.get(&Ident::Intern("fee")),
+            Some(Variant::Int(9500))
+        ));
+    }
+
+    #[test]
+    fn dmn_unsupported() {
+        let model = MODEL
+            .replace("hitPolicy=\"FIRST\"", "hitPolicy=\"ANY\"")
+            .replace("<text>-</text>", "<text>provider.type + 1</text>")
+            .replace("typeRef=\"string\"", "typeRef=\"boolean\"");
+        let errors = decision_tables(&model).unwrap_err();
+        let messages: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+        assert!(
+            messages[0].contains("unsupported hit policy ANY"),
+            "{messages:?}"
+        );
+        assert!(
+            messages[1].contains("unsupported typeRef boolean"),
+            "{messages:?}"
+        );
+
+        let model = MODEL.replace("<text>-</text>", "<text>? > 3</text>");
+        let errors = decision_tables(&model).unwrap_err();
+        assert!(errors[0].to_string().contains("rule r1"));
+        assert!(errors[0].to_string().contains("unsupported FEEL"));
+
+        let model = MODEL.replace("<text>-</text>", "<text>1,</text>");
+        let errors = decision_tables(&model).unwrap_err();
+        assert!(errors[0].to_string().contains("unsupported FEEL '1,'"));
+
+        let model = MODEL.replace(
+            r#"<decisionTable id="t1" hitPolicy="FIRST">"#,
+            r#"<literalExpression><text>1</text></literalExpression><decisionTable id="t1" hitPolicy="FIRST">"#,
+        );
+        let errors = decision_tables(&model).unwrap_err();
+        assert!(errors[0].to_string().contains("only a decision table"));
+    }
+}
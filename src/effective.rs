@@ -0,0 +1,284 @@
+use crate::{
+    propagator::{
+        evaluate_naive_filtered, evaluate_priority_once_filtered, Propagator, Propagators,
+    },
+    property::{Path, Property, PropertyType},
+    table::{Ident, IdentPath, Table},
+    variant::{Error, Variant},
+};
+use chrono::NaiveDate;
+use std::fmt::Display;
+
+/// The range of dates in which a rule is valid.  Both ends are inclusive and either may be open.
+///
+/// Several versions of a rule can be kept in one corpus if their ranges do not overlap.
+/// Evaluating the corpus with `evaluate_naive_as_of` or `evaluate_priority_once_as_of`
+/// then selects the version valid on a given date. For example:
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use ruly::{effective::*, property::{prop, Path, Property}, rule::infer, table::Table};
+/// static ITEM: Property<u32> = prop("item");
+/// static FEE: Property<i64> = prop("fee");
+/// let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+/// let rules = vec![
+///     effective(Effective::ending(date(2024, 6, 30)),
+///         infer(&FEE).from(&ITEM).rule(|_| Some(100))),
+///     effective(Effective::starting(date(2024, 7, 1)),
+///         infer(&FEE).from(&ITEM).rule(|_| Some(105))),
+/// ];
+/// check_versions(&rules).unwrap();
+/// let mut table = Table::new();
+/// table.join_entry(ITEM.name.clone(), 51300.into());
+/// evaluate_priority_once_as_of(&mut table, &rules, &AsOf::Date(date(2024, 7, 1)));
+/// assert_eq!(Path::from(&FEE).query(&table), Some(105));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Effective {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl Effective {
+    /// Valid from the given date onwards.
+    pub fn starting(from: NaiveDate) -> Self {
+        Self {
+            from: Some(from),
+            to: None,
+        }
+    }
+
+    /// Valid up to and including the given date.
+    pub fn ending(to: NaiveDate) -> Self {
+        Self {
+            from: None,
+            to: Some(to),
+        }
+    }
+
+    /// Valid between the given dates, inclusive.
+    pub fn between(from: NaiveDate, to: NaiveDate) -> Self {
+        Self {
+            from: Some(from),
+            to: Some(to),
+        }
+    }
+
+    pub fn contains(&self, date: &NaiveDate) -> bool {
+        self.from.is_none_or(|from| from <= *date) && self.to.is_none_or(|to| *date <= to)
+    }
+
+    /// True iff there is a date in both ranges.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let starts_before = |a: &Self, b: &Self| match (a.from, b.to) {
+            (Some(from), Some(to)) => from <= to,
+            _ => true,
+        };
+        starts_before(self, other) && starts_before(other, self)
+    }
+}
+
+impl Display for Effective {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(from) = self.from {
+            write!(f, "{from}")?;
+        }
+        f.write_str("..")?;
+        if let Some(to) = self.to {
+            write!(f, "{to}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A `Propagator` that is valid only in an `Effective` range.
+struct EffectivePropagator {
+    range: Effective,
+    inner: Box<dyn Propagator>,
+}
+
+/// Limit a propagator to a range of dates.
+pub fn effective(range: Effective, inner: Box<dyn Propagator>) -> Box<dyn Propagator> {
+    Box::new(EffectivePropagator { range, inner })
+}
+
+impl Propagator for EffectivePropagator {
+    fn target(&self) -> &Ident {
+        self.inner.target()
+    }
+
+    fn dependencies(&self) -> Vec<&IdentPath> {
+        self.inner.dependencies()
+    }
+
+    fn fire(&self, state: &Table) -> Option<Variant> {
+        self.inner.fire(state)
+    }
+
+    fn effective(&self) -> Option<&Effective> {
+        Some(&self.range)
+    }
+
+    fn types(&self) -> Vec<(IdentPath, PropertyType)> {
+        self.inner.types()
+    }
+}
+
+/// The date at which rules are selected, given directly or by a date property in the table
+/// such as `service_date`.
+#[derive(Debug, Clone)]
+pub enum AsOf {
+    Date(NaiveDate),
+    Property(IdentPath),
+}
+
+impl AsOf {
+    /// The as-of date, if known.
+    pub fn resolve(&self, table: &Table) -> Option<NaiveDate> {
+        match self {
+            AsOf::Date(date) => Some(*date),
+            AsOf::Property(path) => match table.get_path(path)? {
+                Variant::Date(date) => Some(*date),
+                _ => None,
+            },
+        }
+    }
+
+    // A rule without a range is always valid.  A rule with a range is not
+    // valid until the as-of date is known.
+    fn valid(&self, rule: &dyn Propagator, table: &Table) -> bool {
+        match rule.effective() {
+            None => true,
+            Some(range) => self.resolve(table).is_some_and(|d| range.contains(&d)),
+        }
+    }
+}
+
+impl From<NaiveDate> for AsOf {
+    fn from(value: NaiveDate) -> Self {
+        AsOf::Date(value)
+    }
+}
+
+impl<A> From<&Property<A>> for AsOf {
+    fn from(value: &Property<A>) -> Self {
+        AsOf::Property(IdentPath::new(value.name.clone()))
+    }
+}
+
+impl<A> From<Path<A>> for AsOf
+where
+    A: TryFrom<Variant>,
+{
+    fn from(value: Path<A>) -> Self {
+        AsOf::Property(value.ident_path().clone())
+    }
+}
+
+/// Evaluate rules in priority order like `evaluate_priority_once`,
+/// considering only rules valid at the as-of date.
+pub fn evaluate_priority_once_as_of(table: &mut Table, rules: &Propagators, as_of: &AsOf) -> usize {
+    evaluate_priority_once_filtered(table, rules, |rule, table| as_of.valid(rule, table))
+}
+
+/// Evaluate rules to a fixed point like `evaluate_naive`,
+/// considering only rules valid at the as-of date.
+pub fn evaluate_naive_as_of(
+    table: &mut Table,
+    rules: &Propagators,
+    limit: usize,
+    as_of: &AsOf,
+) -> Result<usize, Error> {
+    evaluate_naive_filtered(table, rules, limit, |rule, table| as_of.valid(rule, table))
+}
+
+/// Check that versions of a rule, having the same target and dependencies, are not
+/// effective on the same date.
+///
+/// A rule without a range is effective on every date, so it overlaps any version with a range.
+/// Rules that both lack a range are not versions and are not compared.
+pub fn check_versions(rules: &Propagators) -> Result<(), Error> {
+    let always = Effective::default();
+    for (i, a) in rules.iter().enumerate() {
+        for b in &rules[i + 1..] {
+            if a.effective().is_none() && b.effective().is_none() {
+                continue;
+            }
+            let x = a.effective().unwrap_or(&always);
+            let y = b.effective().unwrap_or(&always);
+            if a.target() == b.target() && a.dependencies() == b.dependencies() && x.overlaps(y) {
+                Err(Error::Detail(format!(
+                    "versions of rule for {} are both effective in {x} and {y}",
+                    a.target().as_str().unwrap_or_default()
+                )))?
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{property::prop, rule::infer};
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn effective_ranges() {
+        let a = Effective::between(date(2024, 7, 1), date(2024, 10, 31));
+        let b = Effective::starting(date(2024, 11, 1));
+        assert!(a.contains(&date(2024, 10, 31)));
+        assert!(!a.contains(&date(2024, 11, 1)));
+        assert!(!a.overlaps(&b));
+        assert!(b.overlaps(&Effective::default()));
+        assert!(Effective::ending(date(2024, 11, 1)).overlaps(&b));
+        assert_eq!(a.to_string(), "2024-07-01..2024-10-31");
+    }
+
+    #[test]
+    fn versioned_evaluation() {
+        static SERVICE_DATE: Property<NaiveDate> = prop("service_date");
+        static ITEM: Property<u32> = prop("item");
+        static FEE: Property<i64> = prop("fee");
+        let version =
+            |range, fee: i64| effective(range, infer(&FEE).from(&ITEM).rule(move |_| Some(fee)));
+        let rules: Propagators = vec![
+            version(
+                Effective::between(date(2024, 7, 1), date(2024, 10, 31)),
+                100,
+            ),
+            version(Effective::starting(date(2024, 11, 1)), 103),
+        ];
+        check_versions(&rules).unwrap();
+
+        let mut table = Table::new();
+        table.join_entry(ITEM.name.clone(), 51300.into());
+        let as_of = AsOf::from(&SERVICE_DATE);
+        evaluate_naive_as_of(&mut table, &rules, 10, &as_of).unwrap();
+        assert_eq!(Path::from(&FEE).query(&table), None);
+
+        table.join_entry(SERVICE_DATE.name.clone(), date(2024, 11, 5).into());
+        evaluate_naive_as_of(&mut table, &rules, 10, &as_of).unwrap();
+        assert_eq!(Path::from(&FEE).query(&table), Some(103));
+
+        let mut table = Table::new();
+        table.join_entry(ITEM.name.clone(), 51300.into());
+        evaluate_priority_once_as_of(&mut table, &rules, &date(2024, 7, 1).into());
+        assert_eq!(Path::from(&FEE).query(&table), Some(100));
+
+        let overlapping: Propagators = vec![
+            version(Effective::starting(date(2024, 7, 1)), 100),
+            version(Effective::starting(date(2024, 11, 1)), 103),
+        ];
+        assert!(check_versions(&overlapping).is_err());
+
+        let unranged: Propagators = vec![
+            version(Effective::starting(date(2024, 11, 1)), 103),
+            infer(&FEE).from(&ITEM).rule(|_| Some(100)),
+        ];
+        assert!(check_versions(&unranged).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{
+    decision::CellType,
+    schema::{Kind, Schema},
+    table::{Ident, IdentPath, Table},
+    variant::{Error, Variant},
+};
+use std::{fmt::Display, rc::Rc};
+
+/// An error in one cell of a CSV file, identified by line and column header.
+#[derive(Debug, Clone)]
+pub struct CellError {
+    pub line: u64,
+    pub column: String,
+    pub error: Error,
+}
+
+impl Display for CellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.error
+        )
+    }
+}
+
+/// The tables read from a CSV file and the errors in cells that could not be read.
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    pub tables: Vec<Table>,
+    pub errors: Vec<CellError>,
+}
+
+/// Read a CSV file with one `Table` per row.
+///
+/// Each column header is the path of a property, such as `claim/fee` for a property of a nested table.
+/// A cell is read according to the kind of its property in the schema, so that `$12.34` is
+/// an `AUD` amount if that is the kind of the property.  The kind of an undeclared property
+/// is inferred from the text.  A blank cell is absent from the table.
+///
+/// A cell that cannot be read is absent from its table and reported in `Batch::errors`.
+/// Only a bad header or a malformed file is a failure of the whole file.
+pub fn read_csv(reader: impl std::io::Read, schema: &Schema) -> Result<Batch, Error> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+    let columns = reader
+        .headers()
+        .map_err(|e| Error::Detail(e.to_string()))?
+        .iter()
+        .map(|header| {
+            header
+                .parse::<IdentPath>()
+                .map_err(|e| Error::Detail(format!("line 1: {e}")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut batch = Batch::default();
+    for record in reader.records() {
+        let record = record.map_err(|e| Error::Detail(e.to_string()))?;
+        let line = record.position().map(|p| p.line()).unwrap_or_default();
+        let mut table = Table::new();
+        for (i, cell) in record.iter().enumerate() {
+            let Some(path) = columns.get(i) else {
+                batch.errors.push(CellError {
+                    line,
+                    column: format!("{}", i + 1),
+                    error: "cell has no column header".into(),
+                });
+                continue;
+            };
+            if cell.trim().is_empty() {
+                continue;
+            }
+            let value = match schema.get_path(path) {
+                Some(kind) => kind.parse(cell),
+                None => CellType::Infer.parse(cell),
+            };
+            match value {
+                Ok(value) => insert(&mut table, path, value),
+                Err(error) => batch.errors.push(CellError {
+                    line,
+                    column: path.to_string(),
+                    error,
+                }),
+            }
+        }
+        batch.tables.push(table);
+    }
+    Ok(batch)
+}
+
+/// Write tables to a CSV file with one row per table.
+///
+/// Nested tables are flattened so that there is a column for each path present in any table,
+/// in order of the paths.  A value is written according to the kind of its property in
+/// the schema, or in its natural form if undeclared.
+///
+/// A value that cannot be written, such as a `Conflict`, is left blank and reported.
+/// Only a failure to write is a failure of the whole file.
+pub fn write_csv(
+    writer: impl std::io::Write,
+    tables: &[Table],
+    schema: &Schema,
+) -> Result<Vec<CellError>, Error> {
+    let rows: Vec<_> = tables
+        .iter()
+        .map(|table| {
+            let mut cells = Vec::new();
+            flatten(table, None, &mut cells);
+            cells
+        })
+        .collect();
+    let mut columns: Vec<_> = rows.iter().flatten().map(|(path, _)| path).collect();
+    columns.sort_by_cached_key(|path| path.to_string());
+    columns.dedup();
+
+    let mut writer = csv::Writer::from_writer(writer);
+    let failed = |e: csv::Error| Error::Detail(e.to_string());
+    writer
+        .write_record(columns.iter().map(|path| path.to_string()))
+        .map_err(failed)?;
+
+    let mut errors = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let line = i as u64 + 2;
+        let mut record = vec![String::new(); columns.len()];
+        for (path, value) in row {
+            let column = columns.binary_search_by_key(&path.to_string(), |p| p.to_string());
+            let Ok(column) = column else { continue };
+            let text = match schema.get_path(path).cloned().or_else(|| Kind::of(value)) {
+                Some(kind) => kind.format(value),
+                None => Err(Error::Detail(format!("cannot write {value}"))),
+            };
+            match text {
+                Ok(text) => record[column] = text,
+                Err(error) => errors.push(CellError {
+                    line,
+                    column: path.to_string(),
+                    error,
+                }),
+            }
+        }
+        writer.write_record(&record).map_err(failed)?;
+    }
+    writer.flush().map_err(|e| Error::Detail(e.to_string()))?;
+    Ok(errors)
+}
+
+fn flatten<'a>(
+    table: &'a Table,
+    prefix: Option<&IdentPath>,
+    cells: &mut Vec<(IdentPath, &'a Variant)>,
+) {
+    for (name, value) in table.iter() {
+        let path = match prefix {
+            Some(prefix) => prefix.clone().append(name.clone()),
+            None => IdentPath::new(name.clone()),
+        };
+        match value {
+            Variant::Table(inner) => flatten(inner, Some(&path), cells),
+            _ => cells.push((path, value)),
+        }
+    }
+}
+
+fn insert(table: &mut Table, path: &IdentPath, value: Variant) {
+    let mut names: Vec<&Ident> = path.iter().collect();
+    let mut value = value;
+    let mut name = names.pop().expect("a path has at least one ident");
+    while let Some(outer) = names.pop() {
+        let mut inner = Table::new();
+        inner.join_entry(name.clone(), value);
+        value = Variant::Table(Rc::new(inner));
+        name = outer;
+    }
+    table.join_entry(name.clone(), value);
+}
+
+#[cfg(all(test, feature = "quantity"))]
+mod test {
+    use super::*;
+    use crate::{
+        property::{prop, Property},
+        quantity::{money::AUD, quant, Value},
+    };
+    use chrono::NaiveDate;
+
+    #[test]
+    fn csv_round_trip() {
+        static CLAIM: Property<Rc<Table>> = prop("claim");
+        static DATE: Property<NaiveDate> = prop("date");
+        static FEE: Property<Value<AUD>> = quant("fee");
+
+        let claim = Schema::new().with(&DATE, Kind::Date).declare(&FEE);
+        let schema = Schema::new().with(&CLAIM, Kind::Table(Rc::new(claim)));
+        let text = "id,claim/date,claim/fee\nA1,2024-07-01,$12.34\nA2,x,$1\n";
+        let mut batch = read_csv(text.as_bytes(), &schema).unwrap();
+        assert_eq!(batch.tables.len(), 2);
+        assert_eq!(
+            batch.errors[0].to_string(),
+            "line 3, column claim/date: expected Date value, got 'x'"
+        );
+        assert_eq!(batch.errors.len(), 1);
+        let fee = (&CLAIM / &FEE).query(&batch.tables[0]);
+        assert_eq!(fee.map(|f| f.to_string()), Some("$12.34".to_string()));
+
+        let conflict = Variant::Conflict(Box::new(1.into()), Box::new(2.into()));
+        batch.tables[1].join_entry(Ident::Intern("count"), conflict);
+        let mut output = Vec::new();
+        let errors = write_csv(&mut output, &batch.tables, &schema).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 3);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "claim/date,claim/fee,count,id\n2024-07-01,$12.34,,A1\n,$1.00,,A2\n"
+        );
+    }
+}
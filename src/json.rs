@@ -0,0 +1,316 @@
+use crate::{
+    custom,
+    schema::{Kind, Schema},
+    table::{Element, Ident, Set, Table},
+    variant::{Error, Variant},
+};
+use serde_json::{json, Map, Number, Value};
+use std::rc::Rc;
+
+/// Convert a `Variant` to natural JSON, guided by its `Kind` if known.
+///
+/// - Integers and finite floats are JSON numbers. Other floats are the strings `"NaN"`, `"inf"` and `"-inf"`.
+/// - Strings are JSON strings. Dates are ISO 8601 strings e.g. `"2024-07-01"` and instants are RFC 3339 strings.
+/// - A set is an array of its elements.  Integers are numbers and idents and dates are strings.
+/// - A table is an object. Anonymous keys are written `"#n"` and a name beginning with `$` or `#`
+///   is escaped with a leading `$`.  A table whose keys are the anonymous idents `#0` to `#n`
+///   is an array, such as a list of services, unless the array would be read back as a set.
+/// - A quantity kind is written in the form of the quantity e.g. `"$12.34"`.
+/// - `Conflict(a, b)` is the object `{"$conflict": [a, b]}` and `Invalid(e)` is `{"$invalid": "e"}`.
+/// - A custom value is `{"$custom": {"tag": t, "value": v}}` where `v` is its serialized form.
+///
+/// A value that does not match its kind is written in its natural form.
+pub fn to_json(value: &Variant, kind: Option<&Kind>) -> Value {
+    match (value, kind) {
+        (Variant::Conflict(a, b), _) => marker(
+            "$conflict",
+            Value::Array(vec![to_json(a, kind), to_json(b, kind)]),
+        ),
+        (Variant::Invalid(Error::Detail(e)), _) => marker("$invalid", Value::String(e.clone())),
+        (Variant::Table(table), Some(Kind::Table(schema))) => table_to_json(table, schema),
+        (value, Some(kind @ Kind::Quantity(_))) => match kind.format(value) {
+            Ok(text) => Value::String(text),
+            Err(_) => to_json(value, None),
+        },
+        (Variant::String(s), _) => Value::String(s.clone()),
+        (Variant::Date(d), _) => Value::String(d.format("%F").to_string()),
+        (Variant::Instant(t), _) => Value::String(t.to_rfc3339()),
+        (Variant::Float(x), _) => match Number::from_f64(*x) {
+            Some(n) => Value::Number(n),
+            None if x.is_nan() => Value::String("NaN".to_string()),
+            None if *x > 0.0 => Value::String("inf".to_string()),
+            None => Value::String("-inf".to_string()),
+        },
+        (Variant::Int(i), _) => Value::Number((*i).into()),
+        (Variant::Set(set), _) => {
+            let mut elems: Vec<_> = set.iter().collect();
+            elems.sort();
+            Value::Array(elems.into_iter().map(element_to_json).collect())
+        }
+        (Variant::Table(table), _) => table_to_json(table, &Schema::default()),
+        (Variant::Custom(c), _) => match c.to_json() {
+            Ok(v) => marker("$custom", json!({"tag": c.tag(), "value": v})),
+            Err(Error::Detail(e)) => marker("$invalid", Value::String(e)),
+        },
+    }
+}
+
+/// Convert a `Table` to a natural JSON object or array, guided by a `Schema`. See `to_json`.
+pub fn table_to_json(table: &Table, schema: &Schema) -> Value {
+    let items: Option<Vec<Value>> = (0..table.len() as u64)
+        .map(|i| {
+            let name = Ident::Anonymous(i);
+            let value = table.get(&name)?;
+            Some(to_json(value, schema.get(&name)))
+        })
+        .collect();
+    match items {
+        Some(items) if !items.iter().all(|item| element_from_json(item).is_ok()) => {
+            Value::Array(items)
+        }
+        _ => Value::Object(
+            table
+                .iter()
+                .map(|(name, value)| (key_to_json(name), to_json(value, schema.get(name))))
+                .collect(),
+        ),
+    }
+}
+
+/// Convert natural JSON to a `Variant`, guided by its `Kind` if known.  This is the inverse of `to_json`.
+///
+/// Without a kind, a number is an `Int` if it is integral and otherwise a `Float`,
+/// a string is a `String`, an array of integers and strings is a `Set`, an object is a `Table`
+/// and `true` and `false` are `Int` 1 and 0.  Any other array, such as an array of objects,
+/// is a `Table` whose keys are the anonymous idents `#0` to `#n`.
+/// With a kind, a string is read as a value of that kind e.g. a `Date` from `"2024-07-01"`,
+/// and other JSON must be consistent with the kind.
+pub fn from_json(json: &Value, kind: Option<&Kind>) -> Result<Variant, Error> {
+    if let Value::Object(map) = json {
+        if map.len() == 1 {
+            if let Some(pair) = map.get("$conflict") {
+                return match pair {
+                    Value::Array(pair) if pair.len() == 2 => Ok(Variant::Conflict(
+                        Box::new(from_json(&pair[0], kind)?),
+                        Box::new(from_json(&pair[1], kind)?),
+                    )),
+                    _ => Err("$conflict requires an array of two values")?,
+                };
+            }
+            if let Some(custom) = map.get("$custom") {
+                let tag = custom.get("tag").and_then(Value::as_str);
+                return match (tag, custom.get("value")) {
+                    (Some(tag), Some(value)) => {
+                        Ok(Variant::Custom(custom::from_json(tag, value.clone())?))
+                    }
+                    _ => Err("$custom requires a tag and a value")?,
+                };
+            }
+            if let Some(error) = map.get("$invalid") {
+                return match error {
+                    Value::String(e) => Ok(Variant::Invalid(Error::Detail(e.clone()))),
+                    _ => Err("$invalid requires a string")?,
+                };
+            }
+        }
+    }
+
+    let error = || Error::Detail(format!("expected {kind:?} value, got {json}"));
+    Ok(match (json, kind) {
+        (Value::Null, _) => Err("null is not a value")?,
+        (Value::Object(_) | Value::Array(_), Some(Kind::Table(schema))) => {
+            Variant::Table(Rc::new(table_from_json(json, schema)?))
+        }
+        (Value::Object(_), None) => {
+            Variant::Table(Rc::new(table_from_json(json, &Schema::default())?))
+        }
+        (Value::Array(elems), None) => match elems
+            .iter()
+            .map(element_from_json)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(elems) => Variant::Set(Set::new(elems)),
+            Err(_) => Variant::Table(Rc::new(table_from_json(json, &Schema::default())?)),
+        },
+        (Value::String(s), Some(kind)) => kind.parse(s)?,
+        (Value::String(s), None) => Variant::String(s.clone()),
+        (Value::Number(n), Some(Kind::Float)) => Variant::Float(n.as_f64().ok_or_else(error)?),
+        (Value::Number(n), Some(Kind::Int)) => Variant::Int(n.as_i64().ok_or_else(error)?),
+        (Value::Number(n), Some(kind @ Kind::Quantity(_))) => kind.parse(&n.to_string())?,
+        (Value::Number(n), None) => match n.as_i64() {
+            Some(i) => Variant::Int(i),
+            None => Variant::Float(n.as_f64().ok_or_else(error)?),
+        },
+        (Value::Bool(b), Some(Kind::Int) | None) => Variant::Int(*b as i64),
+        (Value::Array(elems), Some(Kind::Set)) => Variant::Set(Set::new(
+            elems
+                .iter()
+                .map(element_from_json)
+                .collect::<Result<Vec<_>, _>>()?,
+        )),
+        _ => Err(error())?,
+    })
+}
+
+/// Convert a natural JSON object or array to a `Table`, guided by a `Schema`. See `from_json`.
+///
+/// The keys of an array are the anonymous idents `#0` to `#n`.  A `null` entry is absent from the table.
+pub fn table_from_json(json: &Value, schema: &Schema) -> Result<Table, Error> {
+    let map = match json {
+        Value::Object(map) => map,
+        Value::Array(items) => {
+            let mut table = Table::new();
+            for (i, value) in items.iter().enumerate() {
+                let name = Ident::Anonymous(i as u64);
+                if value.is_null() {
+                    continue;
+                }
+                let value = from_json(value, schema.get(&name))
+                    .map_err(|e| Error::Detail(format!("#{i}: {e}")))?;
+                table.join_entry(name, value);
+            }
+            return Ok(table);
+        }
+        _ => Err(Error::Detail(format!("expected an object, got {json}")))?,
+    };
+    let mut table = Table::new();
+    for (name, value) in map {
+        if value.is_null() {
+            continue;
+        }
+        if name.starts_with('$') && !name.starts_with("$$") && !name.starts_with("$#") {
+            Err(Error::Detail(format!("reserved key {name}")))?
+        }
+        let name = key_from_json(name);
+        let value = from_json(value, schema.get(&name))
+            .map_err(|e| Error::Detail(format!("{name}: {e}")))?;
+        table.join_entry(name, value);
+    }
+    Ok(table)
+}
+
+/// Convert a set element to JSON: an integer is a number and other elements are strings.
+pub(crate) fn element_to_json(elem: &Element) -> Value {
+    match elem {
+        Element::Int(i) => Value::Number((*i).into()),
+        elem => Value::String(elem.to_string()),
+    }
+}
+
+/// Convert JSON to a set element. A string is read as by `Element::from_str`.
+pub(crate) fn element_from_json(json: &Value) -> Result<Element, Error> {
+    match json {
+        Value::Number(n) if n.is_i64() => Ok(Element::Int(n.as_i64().unwrap_or_default())),
+        Value::String(s) => s.parse(),
+        _ => Err(Error::Detail(format!("expected a set element, got {json}"))),
+    }
+}
+
+fn marker(key: &str, value: Value) -> Value {
+    let mut map = Map::new();
+    map.insert(key.to_string(), value);
+    Value::Object(map)
+}
+
+fn key_to_json(name: &Ident) -> String {
+    match name.as_str() {
+        Some(s) if s.starts_with('$') || s.starts_with('#') => format!("${s}"),
+        Some(s) => s.to_string(),
+        None => format!("#{name}"),
+    }
+}
+
+fn key_from_json(text: &str) -> Ident {
+    if let Some(escaped) = text.strip_prefix('$') {
+        Ident::NonIntern(escaped.to_string())
+    } else if let Some(n) = text.strip_prefix('#').and_then(|n| n.parse().ok()) {
+        Ident::Anonymous(n)
+    } else {
+        Ident::NonIntern(text.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn natural_round_trip() {
+        let json = json!({
+            "count": 3,
+            "ratio": 0.5,
+            "name": "Smith",
+            "tags": ["a", "b"],
+            "$$price": "x",
+            "#7": {"inner": "NaN"},
+            "clash": {"$conflict": [1, "one"]},
+            "bad": {"$invalid": "no fee"}
+        });
+        let table = table_from_json(&json, &Schema::new()).unwrap();
+        assert_eq!(table.len(), 8);
+        assert!(matches!(
+            table.get(&Ident::Anonymous(7)),
+            Some(Variant::Table(_))
+        ));
+        assert!(matches!(
+            table.get(&Ident::Intern("clash")),
+            Some(Variant::Conflict(_, _))
+        ));
+        assert!(matches!(
+            table.get(&Ident::Intern("$price")),
+            Some(Variant::String(_))
+        ));
+        assert_eq!(table_to_json(&table, &Schema::new()), json);
+
+        assert!(table_from_json(&json!({"$price": 1}), &Schema::new()).is_err());
+        assert!(from_json(&json!([{"$conflict": 1}]), None).is_err());
+        assert_eq!(to_json(&Variant::Float(f64::NAN), None), json!("NaN"));
+
+        let json = json!({"services": [{"item": 51300}, {"item": 51303}], "fees": [1.5, 2]});
+        let table = table_from_json(&json, &Schema::new()).unwrap();
+        let services = table
+            .get(&Ident::Intern("services"))
+            .unwrap()
+            .as_table()
+            .unwrap();
+        assert!(matches!(
+            services.get(&Ident::Anonymous(1)),
+            Some(Variant::Table(_))
+        ));
+        assert_eq!(table_to_json(&table, &Schema::new()), json);
+        let json = json!({"#0": 1, "#1": "a"});
+        let table = table_from_json(&json, &Schema::new()).unwrap();
+        assert_eq!(table_to_json(&table, &Schema::new()), json);
+        assert_eq!(to_json(&Variant::Float(f64::NAN), None), json!("NaN"));
+    }
+
+    #[cfg(feature = "quantity")]
+    #[test]
+    fn schema_guided() {
+        use crate::{
+            property::{prop, Property},
+            quantity::{money::AUD, quant, Value as Quant},
+        };
+        static FEE: Property<Quant<AUD>> = quant("fee");
+        static START: Property<chrono::NaiveDate> = prop("start");
+        static COUNT: Property<f64> = prop("count");
+        static CLAIM: Property<Table> = prop("claim");
+
+        let claim = Schema::new()
+            .declare(&FEE)
+            .with(&START, Kind::Date)
+            .with(&COUNT, Kind::Float);
+        let schema = Schema::new().with(&CLAIM, Kind::Table(Rc::new(claim)));
+        let json = json!({"claim": {"fee": "$12.34", "start": "2024-07-01", "count": 2}});
+        let table = table_from_json(&json, &schema).unwrap();
+        let claim = table.get(&CLAIM.name).unwrap().as_table().unwrap();
+        assert!(matches!(claim.get(&FEE.name), Some(Variant::Int(1234))));
+        assert!(matches!(claim.get(&START.name), Some(Variant::Date(_))));
+        assert!(matches!(claim.get(&COUNT.name), Some(Variant::Float(_))));
+        assert_eq!(
+            table_to_json(&table, &schema),
+            json!({"claim": {"fee": "$12.34", "start": "2024-07-01", "count": 2.0}})
+        );
+        assert!(table_from_json(&json!({"claim": {"start": 1}}), &schema).is_err());
+    }
+}
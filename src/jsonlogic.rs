@@ -0,0 +1,469 @@
+use crate::{
+    json::{element_from_json, element_to_json},
+    propagator::{Propagator, Propagators},
+    property::Property,
+    table::{Ident, IdentPath, Set, Table},
+    variant::{Error, Variant},
+};
+use serde_json::{Map, Number, Value};
+
+/// A `Propagator` that interprets a [JSONLogic](https://jsonlogic.com) expression.
+///
+/// The operations `var`, `if` (or `?:`), `==`, `===`, `!=`, `!==`, `<`, `<=`, `>`, `>=`, `!`, `!!`,
+/// `and`, `or`, `+`, `-`, `*`, `/`, `%`, `min`, `max`, `in` and `cat` are supported.
+/// A `var` names a table entry by a path with `.` separating the names of nested tables.
+///
+/// The `var` references are found when the propagator is constructed and are its dependencies.
+/// As for a `Rule`, the expression is evaluated only when all the dependencies are present,
+/// except that a `var` with a default value is optional.
+/// A `var` whose path is computed is not supported.
+///
+/// Table values are presented to the expression as JSON with dates as ISO 8601 strings.
+/// A result of `null` gives no value, a boolean is `Int` 1 or 0, a whole number is `Int`,
+/// and an array of strings is a `Set`.
+pub fn json_logic<A>(target: &Property<A>, logic: &Value) -> Result<Box<dyn Propagator>, Error> {
+    let expr = Expr::parse(logic)?;
+    let mut required = Vec::new();
+    let mut optional = Vec::new();
+    expr.scan(&mut required, &mut optional);
+    for path in &required {
+        optional.retain(|p| p != path);
+    }
+    Ok(Box::new(JsonLogic {
+        target: target.name.clone(),
+        expr,
+        required,
+        optional,
+    }))
+}
+
+/// Construct propagators from a JSON object whose keys are target property names
+/// and whose values are JSONLogic expressions.
+pub fn json_logic_rules(rules: &Value) -> Result<Propagators, Error> {
+    let rules = rules
+        .as_object()
+        .ok_or("expected an object of JSONLogic rules")?;
+    rules
+        .iter()
+        .map(|(name, logic)| {
+            json_logic(&Property::<Variant>::new(name.clone()), logic)
+                .map_err(|e| Error::Detail(format!("rule for {name}: {e}")))
+        })
+        .collect()
+}
+
+struct JsonLogic {
+    target: Ident,
+    expr: Expr,
+    required: Vec<IdentPath>,
+    optional: Vec<IdentPath>,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Literal(Value),
+    Array(Vec<Expr>),
+    Var(IdentPath, Option<Value>),
+    Op(Op, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    If,
+    Eq,
+    StrictEq,
+    Ne,
+    StrictNe,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Not,
+    NotNot,
+    And,
+    Or,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Min,
+    Max,
+    In,
+    Cat,
+}
+
+impl Op {
+    fn parse(name: &str) -> Option<Self> {
+        use Op::*;
+        Some(match name {
+            "if" | "?:" => If,
+            "==" => Eq,
+            "===" => StrictEq,
+            "!=" => Ne,
+            "!==" => StrictNe,
+            "<" => Lt,
+            "<=" => Le,
+            ">" => Gt,
+            ">=" => Ge,
+            "!" => Not,
+            "!!" => NotNot,
+            "and" => And,
+            "or" => Or,
+            "+" => Add,
+            "-" => Sub,
+            "*" => Mul,
+            "/" => Div,
+            "%" => Mod,
+            "min" => Min,
+            "max" => Max,
+            "in" => In,
+            "cat" => Cat,
+            _ => None?,
+        })
+    }
+}
+
+fn var_path(name: &str) -> Result<IdentPath, Error> {
+    let mut names = name.split('.').map(|n| {
+        if n.is_empty() {
+            Err(Error::Detail(format!("invalid var '{name}'")))
+        } else {
+            Ok(Ident::NonIntern(n.to_string()))
+        }
+    });
+    let mut path = IdentPath::new(names.next().unwrap()?);
+    for n in names {
+        path = path.append(n?);
+    }
+    Ok(path)
+}
+
+impl Expr {
+    fn parse(logic: &Value) -> Result<Self, Error> {
+        match logic {
+            Value::Array(items) => Ok(Expr::Array(
+                items.iter().map(Self::parse).collect::<Result<_, _>>()?,
+            )),
+            Value::Object(map) if map.len() == 1 => {
+                let (name, args) = map.iter().next().unwrap();
+                let args = match args {
+                    Value::Array(args) => args.as_slice(),
+                    arg => std::slice::from_ref(arg),
+                };
+                if name == "var" {
+                    let path = match args.first() {
+                        Some(Value::String(path)) => var_path(path)?,
+                        Some(Value::Number(n)) => var_path(&n.to_string())?,
+                        _ => Err("only a literal var path is supported")?,
+                    };
+                    return Ok(Expr::Var(path, args.get(1).cloned()));
+                }
+                let op = Op::parse(name)
+                    .ok_or_else(|| Error::Detail(format!("unsupported operation '{name}'")))?;
+                Ok(Expr::Op(
+                    op,
+                    args.iter().map(Self::parse).collect::<Result<_, _>>()?,
+                ))
+            }
+            Value::Object(_) => Err("expected an operation with a single key")?,
+            literal => Ok(Expr::Literal(literal.clone())),
+        }
+    }
+
+    fn scan(&self, required: &mut Vec<IdentPath>, optional: &mut Vec<IdentPath>) {
+        match self {
+            Expr::Var(path, default) => {
+                let found = if default.is_some() {
+                    optional
+                } else {
+                    required
+                };
+                if !found.contains(path) {
+                    found.push(path.clone())
+                }
+            }
+            Expr::Array(items) | Expr::Op(_, items) => {
+                for item in items {
+                    item.scan(required, optional)
+                }
+            }
+            Expr::Literal(_) => {}
+        }
+    }
+
+    fn eval(&self, state: &Table) -> Result<Value, Error> {
+        match self {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Array(items) => Ok(Value::Array(
+                items
+                    .iter()
+                    .map(|i| i.eval(state))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Expr::Var(path, default) => match state.get_path(path) {
+                Some(value) => to_json(value),
+                None => Ok(default.clone().unwrap_or(Value::Null)),
+            },
+            Expr::Op(op, args) => eval_op(*op, args, state),
+        }
+    }
+}
+
+fn eval_op(op: Op, args: &[Expr], state: &Table) -> Result<Value, Error> {
+    use Op::*;
+    let arg = |i: usize| args.get(i).map_or(Ok(Value::Null), |a| a.eval(state));
+    let all = || {
+        args.iter()
+            .map(|a| a.eval(state))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let numbers = || Ok::<_, Error>(all()?.iter().map(number).collect::<Vec<_>>());
+    let compare =
+        |test: fn(f64, f64) -> bool, strings: fn(&str, &str) -> bool| -> Result<Value, Error> {
+            let values = all()?;
+            let pairs = values.windows(2).take(2);
+            let result = pairs.into_iter().all(|pair| match (&pair[0], &pair[1]) {
+                (Value::String(a), Value::String(b)) => strings(a, b),
+                (a, b) => test(number(a), number(b)),
+            });
+            Ok(Value::Bool(values.len() >= 2 && result))
+        };
+    Ok(match op {
+        If => {
+            let mut i = 0;
+            while i + 1 < args.len() {
+                if truthy(&arg(i)?) {
+                    return arg(i + 1);
+                }
+                i += 2;
+            }
+            if i < args.len() {
+                arg(i)?
+            } else {
+                Value::Null
+            }
+        }
+        Eq => Value::Bool(loose_eq(&arg(0)?, &arg(1)?)),
+        Ne => Value::Bool(!loose_eq(&arg(0)?, &arg(1)?)),
+        StrictEq => Value::Bool(strict_eq(&arg(0)?, &arg(1)?)),
+        StrictNe => Value::Bool(!strict_eq(&arg(0)?, &arg(1)?)),
+        Lt => compare(|a, b| a < b, |a, b| a < b)?,
+        Le => compare(|a, b| a <= b, |a, b| a <= b)?,
+        Gt => compare(|a, b| a > b, |a, b| a > b)?,
+        Ge => compare(|a, b| a >= b, |a, b| a >= b)?,
+        Not => Value::Bool(!truthy(&arg(0)?)),
+        NotNot => Value::Bool(truthy(&arg(0)?)),
+        And | Or => {
+            let mut last = Value::Null;
+            for a in args {
+                last = a.eval(state)?;
+                if truthy(&last) == matches!(op, Or) {
+                    break;
+                }
+            }
+            last
+        }
+        Add => from_f64(numbers()?.iter().sum()),
+        Mul => from_f64(numbers()?.iter().product()),
+        Sub => match numbers()?[..] {
+            [a] => from_f64(-a),
+            [a, b, ..] => from_f64(a - b),
+            [] => Value::Null,
+        },
+        Div => from_f64(number(&arg(0)?) / number(&arg(1)?)),
+        Mod => from_f64(number(&arg(0)?) % number(&arg(1)?)),
+        Min => from_f64(numbers()?.into_iter().reduce(f64::min).unwrap_or(f64::NAN)),
+        Max => from_f64(numbers()?.into_iter().reduce(f64::max).unwrap_or(f64::NAN)),
+        In => Value::Bool(match (arg(0)?, arg(1)?) {
+            (Value::String(a), Value::String(b)) => b.contains(&a),
+            (a, Value::Array(items)) => items.iter().any(|i| strict_eq(&a, i)),
+            _ => false,
+        }),
+        Cat => Value::String(all()?.iter().map(text).collect()),
+    })
+}
+
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn number(value: &Value) -> f64 {
+    match value {
+        Value::Null => 0.0,
+        Value::Bool(b) => *b as i64 as f64,
+        Value::Number(n) => n.as_f64().unwrap_or(f64::NAN),
+        Value::String(s) if s.trim().is_empty() => 0.0,
+        Value::String(s) => s.trim().parse().unwrap_or(f64::NAN),
+        _ => f64::NAN,
+    }
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn from_f64(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        Value::from(value as i64)
+    } else {
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn strict_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+// Equality with the type coercions of JavaScript `==` for scalars.
+fn loose_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Null, Value::Null) => true,
+        (Value::Null, _) | (_, Value::Null) => false,
+        (Value::String(x), Value::String(y)) => x == y,
+        (
+            Value::Number(_) | Value::Bool(_) | Value::String(_),
+            Value::Number(_) | Value::Bool(_) | Value::String(_),
+        ) => number(a) == number(b),
+        _ => a == b,
+    }
+}
+
+fn to_json(value: &Variant) -> Result<Value, Error> {
+    Ok(match value {
+        Variant::String(s) => Value::String(s.clone()),
+        Variant::Int(i) => Value::from(*i),
+        Variant::Float(f) => Number::from_f64(*f).map_or(Value::Null, Value::Number),
+        Variant::Date(d) => Value::String(d.format("%F").to_string()),
+        Variant::Instant(t) => Value::String(t.to_rfc3339()),
+        Variant::Set(set) => Value::Array(set.iter().map(element_to_json).collect()),
+        Variant::Table(table) => {
+            let mut map = Map::new();
+            for (name, value) in table.iter() {
+                map.insert(name.to_string(), to_json(value)?);
+            }
+            Value::Object(map)
+        }
+        Variant::Custom(c) => c.to_json()?,
+        Variant::Invalid(e) => Err(e.clone())?,
+        Variant::Conflict(_, _) => Err("conflicting values")?,
+    })
+}
+
+fn from_json(value: Value) -> Result<Option<Variant>, Error> {
+    Ok(Some(match value {
+        Value::Null => return Ok(None),
+        Value::Bool(b) => Variant::Int(b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Variant::Int(i),
+            None => Variant::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => Variant::String(s),
+        Value::Array(items) => Variant::Set(Set::new(
+            items
+                .iter()
+                .map(element_from_json)
+                .collect::<Result<Vec<_>, _>>()?,
+        )),
+        Value::Object(_) => Err("unsupported object result")?,
+    }))
+}
+
+impl Propagator for JsonLogic {
+    fn target(&self) -> &Ident {
+        &self.target
+    }
+
+    fn dependencies(&self) -> Vec<&IdentPath> {
+        self.required.iter().chain(&self.optional).collect()
+    }
+
+    fn fire(&self, state: &Table) -> Option<Variant> {
+        for path in &self.required {
+            state.get_path(path)?;
+        }
+        match self.expr.eval(state).and_then(from_json) {
+            Ok(value) => value,
+            Err(e) => Some(Variant::Invalid(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{propagator::evaluate_naive, property::prop};
+    use serde_json::json;
+    use std::rc::Rc;
+
+    fn eval(logic: Value, table: &Table) -> Option<Variant> {
+        static RESULT: Property<Variant> = prop("result");
+        json_logic(&RESULT, &logic).unwrap().fire(table)
+    }
+
+    #[test]
+    fn json_logic_operations() {
+        let mut patient = Table::new();
+        patient.join_entry(Ident::Intern("age"), 67.into());
+        let mut table = Table::new();
+        table.join_entry(Ident::Intern("patient"), Rc::new(patient).into());
+        table.join_entry(Ident::Intern("item"), 51300.into());
+        table.join_entry(Ident::Intern("provider"), "GP".to_string().into());
+
+        let senior = json!({"if": [{">=": [{"var": "patient.age"}, 65]}, "senior", "adult"]});
+        assert!(matches!(eval(senior, &table), Some(Variant::String(s)) if s == "senior"));
+        let fee = json!({"*": [{"var": "item"}, 2, 0.5]});
+        assert!(matches!(eval(fee, &table), Some(Variant::Int(51300))));
+        let listed = json!({"and": [{"in": [{"var": "provider"}, ["GP", "specialist"]]}, {"==": [{"var": "item"}, "51300"]}]});
+        assert!(matches!(eval(listed, &table), Some(Variant::Int(1))));
+        let between = json!({"<": [1, {"var": "patient.age"}, 65]});
+        assert!(matches!(eval(between, &table), Some(Variant::Int(0))));
+        let optional = json!({"or": [{"var": ["referral", "none"]}, "x"]});
+        assert!(matches!(eval(optional, &table), Some(Variant::String(s)) if s == "none"));
+        assert!(eval(json!({"var": "missing"}), &table).is_none());
+        assert!(eval(json!({"/": [1, 3]}), &table).is_some_and(|v| matches!(v, Variant::Float(_))));
+        assert!(eval(json!({"cat": ["a", {"var": "item"}]}), &table)
+            .is_some_and(|v| v.to_string() == "a51300"));
+    }
+
+    #[test]
+    fn json_logic_dependencies() {
+        let rules = json_logic_rules(&json!({
+            "assist_fee": {"if": [{"==": [{"var": "item"}, 51303]}, {"*": [{"var": "surgeon_fee"}, 0.2]}, null]},
+            "surgeon_fee": {"var": ["schedule.fee", 0]},
+        }))
+        .unwrap();
+        let deps: Vec<_> = rules[0]
+            .dependencies()
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(deps, ["item", "surgeon_fee"]);
+        assert_eq!(rules[1].dependencies()[0].to_string(), "schedule/fee");
+
+        let mut table = Table::new();
+        table.join_entry(Ident::Intern("item"), 51303.into());
+        evaluate_naive(&mut table, &rules, 5).unwrap();
+        assert!(matches!(
+            table.get(&Ident::Intern("assist_fee")),
+            Some(Variant::Int(0))
+        ));
+
+        assert!(json_logic_rules(&json!({"x": {"var": {"cat": ["a", "b"]}}})).is_err());
+        assert!(json_logic_rules(&json!({"x": {"reduce": [1, 2]}})).is_err());
+    }
+}
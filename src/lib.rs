@@ -1,6 +1,18 @@
+pub mod custom;
+pub mod decision;
+#[cfg(feature = "dmn")]
+pub mod dmn;
+pub mod effective;
+#[cfg(feature = "csv")]
+pub mod flat;
+pub mod json;
+pub mod jsonlogic;
+pub mod lookup;
 pub mod propagator;
 pub mod property;
 pub mod quantity;
+pub mod query;
 pub mod rule;
+pub mod schema;
 pub mod table;
 pub mod variant;
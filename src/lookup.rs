@@ -0,0 +1,326 @@
+use crate::{
+    propagator::{Propagator, Propagators},
+    property::{Path, Property},
+    table::{Ident, IdentPath, Table},
+    variant::{Error, Variant},
+};
+use chrono::NaiveDate;
+use std::{collections::HashMap, rc::Rc};
+
+/// A reference dataset of rows with named columns, such as a provider registry.
+///
+/// A missing cell is `None`.  A dataset is joined to a `Table` by `lookup`.
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    columns: Vec<Ident>,
+    rows: Vec<Vec<Option<Variant>>>,
+}
+
+impl Dataset {
+    /// An empty dataset with the given columns.
+    pub fn new(columns: impl IntoIterator<Item = impl Into<Ident>>) -> Self {
+        Self {
+            columns: columns.into_iter().map(Into::into).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row with a cell for each column.
+    pub fn push(&mut self, row: Vec<Option<Variant>>) -> Result<(), Error> {
+        if row.len() != self.columns.len() {
+            Err("wrong number of cells in dataset row")?
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    /// The number of rows
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// True iff there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    fn column(&self, name: &Ident) -> Result<usize, Error> {
+        self.columns.iter().position(|c| c == name).ok_or_else(|| {
+            Error::Detail(format!(
+                "no column {} in dataset",
+                name.as_str().unwrap_or_default()
+            ))
+        })
+    }
+
+    /// Read a dataset from CSV.  The header names the columns, which may be typed
+    /// as for a `decision::DecisionTable` e.g. `provider:string`.  Blank cells are missing.
+    /// All errors are reported, identified by line number.
+    #[cfg(feature = "csv")]
+    pub fn from_csv(reader: impl std::io::Read) -> Result<Self, Vec<Error>> {
+        use crate::decision::Column;
+
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .from_reader(reader);
+        let header = reader
+            .headers()
+            .map_err(|e| vec![Error::Detail(e.to_string())])?
+            .clone();
+        let mut errors = Vec::new();
+        let mut columns = Vec::new();
+        for name in header.iter() {
+            match name.parse::<Column>() {
+                Ok(column) => columns.push(column),
+                Err(e) => errors.push(Error::Detail(format!("line 1: {e}"))),
+            }
+        }
+        let mut dataset = Self::new(columns.iter().map(|c| c.path.subject().clone()));
+
+        for record in reader.records() {
+            let record = match record {
+                Ok(record) => record,
+                Err(e) => {
+                    errors.push(Error::Detail(e.to_string()));
+                    continue;
+                }
+            };
+            let line = record.position().map(|p| p.line()).unwrap_or_default();
+            let row = record
+                .iter()
+                .zip(&columns)
+                .map(|(cell, column)| match cell {
+                    "" => Ok(None),
+                    _ => column.cell_type.parse(cell).map(Some),
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|row| dataset.push(row));
+            if let Err(e) = row {
+                errors.push(Error::Detail(format!("line {line}: {e}")));
+            }
+        }
+        if errors.is_empty() {
+            Ok(dataset)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// The hashable form of a key value.  Keys are compared by type, so that
+/// the string `"42"` does not match the integer `42`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    String(String),
+    Int(i64),
+    Date(NaiveDate),
+}
+
+impl Key {
+    fn new(value: &Variant) -> Option<Self> {
+        match value {
+            Variant::String(s) => Some(Key::String(s.clone())),
+            Variant::Int(i) => Some(Key::Int(*i)),
+            Variant::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => {
+                Some(Key::Int(*f as i64))
+            }
+            Variant::Date(d) => Some(Key::Date(*d)),
+            _ => None,
+        }
+    }
+}
+
+/// Join rows of a reference dataset into a table, matching key columns to key properties.
+///
+/// When all the key properties are present in the table and a row has equal key columns,
+/// each joined column of the row is the value of its target property.
+/// The dataset is indexed by a hash of its keys when the propagators are built
+/// so that a lookup takes constant time, however large the dataset.
+///
+/// For example:
+///
+/// `lookup(providers).key("provider_number", &PROVIDER).join("specialty", &SPECIALTY).build()`
+pub fn lookup(dataset: Dataset) -> Lookup {
+    Lookup {
+        dataset,
+        keys: Vec::new(),
+        joins: Vec::new(),
+    }
+}
+
+/// A builder for lookup propagators. See `lookup`.
+pub struct Lookup {
+    dataset: Dataset,
+    keys: Vec<(Ident, IdentPath)>,
+    joins: Vec<(Ident, Ident)>,
+}
+
+struct Index {
+    keys: Vec<IdentPath>,
+    rows: Vec<Vec<Option<Variant>>>,
+    index: HashMap<Vec<Key>, usize>,
+}
+
+impl Lookup {
+    /// Match a key column of the dataset with a property in the table.
+    pub fn key<A>(mut self, column: impl Into<Ident>, path: impl Into<Path<A>>) -> Self
+    where
+        A: TryFrom<Variant>,
+    {
+        self.keys
+            .push((column.into(), path.into().ident_path().clone()));
+        self
+    }
+
+    /// Join a column of the dataset into a target property.
+    pub fn join<A>(mut self, column: impl Into<Ident>, target: &Property<A>) -> Self {
+        self.joins.push((column.into(), target.name.clone()));
+        self
+    }
+
+    /// Index the dataset and construct a `Propagator` for each joined column.
+    /// It is an error if a column is missing or two rows have the same keys.
+    pub fn build(self) -> Result<Propagators, Error> {
+        if self.keys.is_empty() {
+            Err("lookup requires a key")?
+        }
+        let key_columns = self
+            .keys
+            .iter()
+            .map(|(c, _)| self.dataset.column(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        let joins = self
+            .joins
+            .iter()
+            .map(|(c, t)| Ok((self.dataset.column(c)?, t.clone())))
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut index = HashMap::with_capacity(self.dataset.rows.len());
+        for (i, row) in self.dataset.rows.iter().enumerate() {
+            let key = key_columns
+                .iter()
+                .map(|c| row[*c].as_ref().and_then(Key::new))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(|| Error::Detail(format!("dataset row {} has no key", i + 1)))?;
+            if index.insert(key, i).is_some() {
+                Err(Error::Detail(format!(
+                    "dataset row {} has a duplicate key",
+                    i + 1
+                )))?
+            }
+        }
+
+        let index = Rc::new(Index {
+            keys: self.keys.into_iter().map(|(_, p)| p).collect(),
+            rows: self.dataset.rows,
+            index,
+        });
+        Ok(joins
+            .into_iter()
+            .map(|(column, target)| {
+                Box::new(LookupColumn {
+                    index: index.clone(),
+                    column,
+                    target,
+                }) as Box<dyn Propagator>
+            })
+            .collect())
+    }
+}
+
+/// A `Propagator` for one joined column of a `Lookup`.
+struct LookupColumn {
+    index: Rc<Index>,
+    column: usize,
+    target: Ident,
+}
+
+impl Propagator for LookupColumn {
+    fn target(&self) -> &Ident {
+        &self.target
+    }
+
+    fn dependencies(&self) -> Vec<&IdentPath> {
+        self.index.keys.iter().collect()
+    }
+
+    fn fire(&self, state: &Table) -> Option<Variant> {
+        let key = self
+            .index
+            .keys
+            .iter()
+            .map(|p| Key::new(state.get_path(p)?))
+            .collect::<Option<Vec<_>>>()?;
+        let row = self.index.index.get(&key)?;
+        self.index.rows[*row][self.column].clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{propagator::evaluate_naive, property::prop};
+
+    #[test]
+    fn dataset_lookup() {
+        static PROVIDER: Property<String> = prop("provider");
+        static STATE: Property<String> = prop("state");
+        static SPECIALTY: Property<String> = prop("specialty");
+        static ITEM: Property<i64> = prop("item");
+        static FEE: Property<i64> = prop("fee");
+
+        let mut providers = Dataset::new(["number", "state", "specialty", "fee"]);
+        for (number, state, specialty, fee) in [
+            ("2423451A", "NSW", Some("surgery"), 31035),
+            ("2423451A", "VIC", None, 30000),
+            ("0987654B", "NSW", Some("anaesthesia"), 9500),
+        ] {
+            providers
+                .push(vec![
+                    Some(number.to_string().into()),
+                    Some(state.to_string().into()),
+                    specialty.map(|s| s.to_string().into()),
+                    Some(fee.into()),
+                ])
+                .unwrap();
+        }
+        let rules = lookup(providers.clone())
+            .key("number", &PROVIDER)
+            .key("state", &STATE)
+            .join("specialty", &SPECIALTY)
+            .join("fee", &FEE)
+            .build()
+            .unwrap();
+
+        let mut table = Table::new();
+        table.join_entry(PROVIDER.name.clone(), "2423451A".to_string().into());
+        table.join_entry(STATE.name.clone(), "NSW".to_string().into());
+        evaluate_naive(&mut table, &rules, 5).unwrap();
+        assert_eq!(Path::from(&SPECIALTY).query(&table).unwrap(), "surgery");
+        assert_eq!(Path::from(&FEE).query(&table), Some(31035));
+
+        let mut table = Table::new();
+        table.join_entry(PROVIDER.name.clone(), "2423451A".to_string().into());
+        table.join_entry(STATE.name.clone(), "VIC".to_string().into());
+        evaluate_naive(&mut table, &rules, 5).unwrap();
+        assert_eq!(Path::from(&SPECIALTY).query(&table), None);
+        assert_eq!(Path::from(&FEE).query(&table), Some(30000));
+
+        assert!(lookup(providers.clone())
+            .key("number", &PROVIDER)
+            .build()
+            .is_err());
+        assert!(lookup(providers).key("item", &ITEM).build().is_err());
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn dataset_from_csv() {
+        let items =
+            Dataset::from_csv("item:int,description\n51300,Assist\n51303,\n".as_bytes()).unwrap();
+        assert_eq!(items.len(), 2);
+        let errors =
+            Dataset::from_csv("item:int,description\nx,Assist\n1\n".as_bytes()).unwrap_err();
+        assert_eq!(errors.len(), 2);
+    }
+}
@@ -1,4 +1,6 @@
 use crate::{
+    effective::Effective,
+    property::PropertyType,
     table::{Ident, IdentPath, Table},
     variant::{Error, Variant},
 };
@@ -15,6 +17,14 @@ pub trait Propagator {
     fn dependencies(&self) -> Vec<&IdentPath>;
     /// Evaluate a new value based on the current values in the `Table`.
     fn fire(&self, state: &Table) -> Option<Variant>;
+    /// The range of dates in which this propagator is valid, if limited.
+    fn effective(&self) -> Option<&Effective> {
+        None
+    }
+    /// The paths of the target and dependencies with the types used for them, if known.
+    fn types(&self) -> Vec<(IdentPath, PropertyType)> {
+        Vec::new()
+    }
 }
 
 /// A corpus of propagators
@@ -33,6 +43,7 @@ where
     F: Fn(&[Option<&Variant>]) -> Option<Variant> + 'static,
 {
     /// Create a general `Propagator`
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(
         target: Ident,
         deps: impl IntoIterator<Item = IdentPath>,
@@ -72,10 +83,19 @@ where
 /// Evaluate rules in priority order. The first result for a given property stands.  
 /// Each rule is evaluated at most once and no joins are performed.  
 pub fn evaluate_priority_once(table: &mut Table, rules: &Propagators) -> usize {
+    evaluate_priority_once_filtered(table, rules, |_, _| true)
+}
+
+/// Evaluate rules in priority order, skipping those for which `valid` is false.
+pub(crate) fn evaluate_priority_once_filtered(
+    table: &mut Table,
+    rules: &Propagators,
+    valid: impl Fn(&dyn Propagator, &Table) -> bool,
+) -> usize {
     let mut changes = 0;
     for rule in rules {
-        if table.get(rule.target()).is_none() {
-            if let Some(b) = rule.fire(&table) {
+        if table.get(rule.target()).is_none() && valid(rule.as_ref(), table) {
+            if let Some(b) = rule.fire(table) {
                 table.join_entry(rule.target().clone(), b);
                 changes += 1;
             }
@@ -94,6 +114,16 @@ pub fn evaluate_naive(
     table: &mut Table,
     rules: &Propagators,
     limit: usize,
+) -> Result<usize, Error> {
+    evaluate_naive_filtered(table, rules, limit, |_, _| true)
+}
+
+/// Naive evaluation, skipping rules for which `valid` is false.
+pub(crate) fn evaluate_naive_filtered(
+    table: &mut Table,
+    rules: &Propagators,
+    limit: usize,
+    valid: impl Fn(&dyn Propagator, &Table) -> bool,
 ) -> Result<usize, Error> {
     let mut iteration = 0;
     loop {
@@ -105,7 +135,10 @@ pub fn evaluate_naive(
         let mut changes = 0;
 
         for rule in rules {
-            if let Some(value) = rule.fire(&table) {
+            if !valid(rule.as_ref(), table) {
+                continue;
+            }
+            if let Some(value) = rule.fire(table) {
                 if table.join_entry(rule.target().clone(), value) {
                     changes += 1
                 }
@@ -117,3 +150,80 @@ pub fn evaluate_naive(
         }
     }
 }
+
+/// Check that each property is used with one type throughout a corpus of rules.
+///
+/// Properties are identified by name, so a name declared with different types in different
+/// places refers to one table entry and the rules that disagree about its type do not fire.
+/// Every path used with more than one type, as a target or a dependency, is reported.
+/// Types with the same `Variant` representation, such as `u32` and `i64`, do not conflict.
+/// Propagators that do not declare their types, such as decision tables, are not checked.
+pub fn check_types(rules: &Propagators) -> Result<(), Vec<Error>> {
+    let mut uses: Vec<(IdentPath, Vec<PropertyType>)> = Vec::new();
+    for (path, ty) in rules.iter().flat_map(|rule| rule.types()) {
+        match uses.iter_mut().find(|(p, _)| *p == path) {
+            Some((_, types)) if !types.contains(&ty) => types.push(ty),
+            Some(_) => {}
+            None => uses.push((path, vec![ty])),
+        }
+    }
+    let errors: Vec<_> = uses
+        .into_iter()
+        .filter(|(_, types)| types.len() > 1)
+        .map(|(path, types)| {
+            let types: Vec<_> = types.iter().map(|t| t.to_string()).collect();
+            Error::Detail(format!("{path} is used as {}", types.join(" and ")))
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(all(test, feature = "quantity"))]
+mod test {
+    use super::*;
+    use crate::{
+        property::{prop, Property},
+        quantity::{date::Date, money::AUD, quant, Value},
+        rule::infer,
+    };
+
+    #[test]
+    fn conflicting_types() {
+        static ITEM: Property<u32> = prop("item");
+        static RATE: Property<f64> = prop("assist_51303");
+        static FEE: Property<Value<AUD>> = quant("fee");
+        static ASSIST: Property<Value<AUD>> = quant("assist_51303");
+
+        let rules: Propagators = vec![
+            infer(&FEE).from(&ITEM).from(&RATE).rule(|_| None),
+            infer(&FEE).from(&ITEM).rule(|_| None),
+        ];
+        check_types(&rules).unwrap();
+
+        let rules: Propagators = vec![
+            infer(&FEE).from(&ITEM).from(&RATE).rule(|_| None),
+            infer(&ASSIST).from(&ITEM).rule(|_| None),
+        ];
+        let errors = check_types(&rules).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        let error = errors[0].to_string();
+        assert!(error.starts_with("assist_51303 is used as f64 and "));
+        assert!(error.ends_with("AUD>"));
+
+        static ITEM_NUMBER: Property<i64> = prop("item");
+        static SERVICE_DATE: Property<chrono::NaiveDate> = prop("service_date");
+        static SERVICE_DAY: Property<Value<Date>> = quant("service_date");
+        let rules: Propagators = vec![
+            infer(&FEE).from(&ITEM).from(&SERVICE_DATE).rule(|_| None),
+            infer(&ASSIST)
+                .from(&ITEM_NUMBER)
+                .from(&SERVICE_DAY)
+                .rule(|_| None),
+        ];
+        check_types(&rules).unwrap();
+    }
+}
@@ -1,17 +1,23 @@
+#[cfg(feature = "quantity")]
+use crate::quantity::Value;
 use crate::{
     table::{Ident, IdentPath, Table},
     variant::Variant,
 };
-use std::{marker::PhantomData, ops::Div, rc::Rc};
+#[cfg(feature = "quantity")]
+use chrono::{DateTime, NaiveDate, Utc};
+use std::{any::TypeId, fmt::Display, marker::PhantomData, ops::Div, rc::Rc};
 
 /// A property confers a meaning to a value, its interpretation or what it represents.
 /// A property has a name or `Ident` that identifies it uniquely.
 /// Two properties that have the same name represent the same thing and are equal.
-/// They should have the same type (but this is not enforced).
+/// They should have the same type.  This is not enforced by the compiler but
+/// `propagator::check_types` reports names used with different types in a corpus of rules.
 #[derive(Eq, Hash, Debug)]
 pub struct Property<A> {
     pub name: Ident,
-    marker: PhantomData<A>,
+    // A function type keeps `Property` `Sync` so that it can be a static for any `A`.
+    marker: PhantomData<fn() -> A>,
 }
 
 impl<A> Clone for Property<A> {
@@ -47,6 +53,82 @@ pub const fn prop<A>(name: &'static str) -> Property<A> {
     }
 }
 
+/// The Rust type of a property's values, as used by a `Rule`.
+///
+/// Types that read and write the same `Variant` are equal, such as `u32` and `i64`
+/// or `NaiveDate` and `Value<Date>`.  Distinct quantities, such as `Value<AUD>` and `i64`, are not.
+#[derive(Debug, Clone, Copy)]
+pub struct PropertyType {
+    repr: TypeId,
+    name: &'static str,
+}
+
+impl PropertyType {
+    pub fn of<A: 'static>() -> Self {
+        Self {
+            repr: representative(TypeId::of::<A>()),
+            name: std::any::type_name::<A>(),
+        }
+    }
+}
+
+/// The type standing for a group of types with the same `Variant` representation.
+fn representative(id: TypeId) -> TypeId {
+    let groups = [
+        (
+            TypeId::of::<i64>(),
+            Vec::from([TypeId::of::<i32>(), TypeId::of::<u32>()]),
+        ),
+        #[cfg(feature = "quantity")]
+        (TypeId::of::<NaiveDate>(), quantity_dates()),
+        #[cfg(feature = "quantity")]
+        (TypeId::of::<DateTime<Utc>>(), quantity_instants()),
+    ];
+    groups
+        .into_iter()
+        .find_map(|(repr, members)| members.contains(&id).then_some(repr))
+        .unwrap_or(id)
+}
+
+#[cfg(feature = "quantity")]
+fn quantity_dates() -> Vec<TypeId> {
+    use crate::quantity::date::{Au, Iso, LocalDate, Us};
+    Vec::from([
+        TypeId::of::<Value<LocalDate<Au>>>(),
+        TypeId::of::<Value<LocalDate<Us>>>(),
+        TypeId::of::<Value<LocalDate<Iso>>>(),
+    ])
+}
+
+#[cfg(feature = "quantity")]
+fn quantity_instants() -> Vec<TypeId> {
+    use crate::quantity::instant::*;
+    Vec::from([
+        TypeId::of::<Value<Instant>>(),
+        TypeId::of::<Value<ZonedInstant<Sydney>>>(),
+        TypeId::of::<Value<ZonedInstant<Melbourne>>>(),
+        TypeId::of::<Value<ZonedInstant<Brisbane>>>(),
+        TypeId::of::<Value<ZonedInstant<Adelaide>>>(),
+        TypeId::of::<Value<ZonedInstant<Perth>>>(),
+        TypeId::of::<Value<ZonedInstant<Hobart>>>(),
+        TypeId::of::<Value<ZonedInstant<Darwin>>>(),
+    ])
+}
+
+impl PartialEq for PropertyType {
+    fn eq(&self, other: &Self) -> bool {
+        self.repr == other.repr
+    }
+}
+
+impl Eq for PropertyType {}
+
+impl Display for PropertyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
 /// A `Path` designates a property that may be in a nested `Table`.
 /// Tables can be nested to any depth because a `Variant` value can be a `Table`.
 /// A `Path` is constructed by connecting `Property`s with the `/` operator.
@@ -54,7 +136,7 @@ pub const fn prop<A>(name: &'static str) -> Property<A> {
 #[derive(Debug, Clone)]
 pub struct Path<A> {
     inner: IdentPath,
-    marker: PhantomData<A>,
+    marker: PhantomData<fn() -> A>,
 }
 
 impl<A> Path<A>
@@ -92,10 +174,10 @@ impl<A> Div<&Property<A>> for Path<Rc<Table>> {
     }
 }
 
-impl<A> Into<Path<A>> for &Property<A> {
-    fn into(self) -> Path<A> {
+impl<A> From<&Property<A>> for Path<A> {
+    fn from(value: &Property<A>) -> Self {
         Path::<A> {
-            inner: IdentPath::new(self.name.clone()),
+            inner: IdentPath::new(value.name.clone()),
             marker: PhantomData,
         }
     }
@@ -0,0 +1,357 @@
+use super::{
+    date::{Date, DateLocale, LocalDate},
+    Quantity, Value,
+};
+use crate::variant::Error;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+    str::FromStr,
+};
+
+/// An Australian state or territory, which determines the public holidays in a `Calendar`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum State {
+    Act,
+    Nsw,
+    Nt,
+    Qld,
+    Sa,
+    Tas,
+    Vic,
+    Wa,
+}
+
+impl FromStr for State {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use State::*;
+        Ok(match s.trim().to_uppercase().as_str() {
+            "ACT" => Act,
+            "NSW" => Nsw,
+            "NT" => Nt,
+            "QLD" => Qld,
+            "SA" => Sa,
+            "TAS" => Tas,
+            "VIC" => Vic,
+            "WA" => Wa,
+            _ => Err(Error::Detail(format!("unknown state '{s}'")))?,
+        })
+    }
+}
+
+/// A calendar of business days: weekdays that are not public holidays.
+///
+/// Holidays are computed for national holidays and, optionally, those of a `State`
+/// following the rules currently observed, including Easter and substitute days
+/// when a holiday falls on a weekend.
+/// Further holidays, such as a one-off proclamation, can be loaded with `with_holidays`.
+///
+/// The holidays for each year are computed once and cached.  A calendar can be shared
+/// among rules with `Rc`:
+///
+/// ```
+/// use ruly::quantity::{calendar::{Calendar, State}, date::Date, Value};
+/// let calendar = Calendar::state(State::Nsw);
+/// let lodged: Value<Date> = "28/03/2024".parse().unwrap();
+/// let deadline = calendar.add_business_days(&lodged, 2).unwrap();
+/// assert_eq!(deadline.to_string(), "03/04/2024");
+/// ```
+#[derive(Debug, Default)]
+pub struct Calendar {
+    state: Option<State>,
+    custom: BTreeMap<NaiveDate, String>,
+    cache: RefCell<HashMap<i32, Rc<BTreeMap<NaiveDate, String>>>>,
+}
+
+impl Calendar {
+    /// A calendar with national public holidays only.
+    pub fn national() -> Self {
+        Self::default()
+    }
+
+    /// A calendar with the public holidays of a state or territory.
+    pub fn state(state: State) -> Self {
+        Self {
+            state: Some(state),
+            ..Self::default()
+        }
+    }
+
+    /// Add holidays listed one per line as a date, optionally followed by a comma and a name.
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn with_holidays(mut self, text: &str) -> Result<Self, Error> {
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (date, name) = line.split_once(',').unwrap_or((line, "holiday"));
+            self.custom
+                .insert(Date::parse(date)?, name.trim().to_string());
+        }
+        self.cache.borrow_mut().clear();
+        Ok(self)
+    }
+
+    /// The holidays in a year with their names.
+    pub fn holidays(&self, year: i32) -> Rc<BTreeMap<NaiveDate, String>> {
+        self.cache
+            .borrow_mut()
+            .entry(year)
+            .or_insert_with(|| {
+                let mut holidays = public_holidays(year, self.state);
+                for (date, name) in self.custom.iter().filter(|(d, _)| d.year() == year) {
+                    holidays.entry(*date).or_insert_with(|| name.clone());
+                }
+                Rc::new(holidays)
+            })
+            .clone()
+    }
+
+    fn is_business(&self, date: NaiveDate) -> bool {
+        !is_weekend(date) && !self.holidays(date.year()).contains_key(&date)
+    }
+
+    pub fn is_business_day<L: DateLocale>(&self, date: &Value<LocalDate<L>>) -> bool {
+        self.is_business(date.0)
+    }
+
+    /// The date that is a number of business days after the given date, or before it if negative.
+    /// The given date need not be a business day.  It is an error if the result is out of range.
+    pub fn add_business_days<L: DateLocale>(
+        &self,
+        date: &Value<LocalDate<L>>,
+        days: i64,
+    ) -> Result<Value<LocalDate<L>>, Error> {
+        let error = || Error::Detail(format!("{date} plus {days} business days is out of range"));
+        let count = days.unsigned_abs();
+        // The result is at least as far away as the same number of calendar days.
+        if days < 0 {
+            date.0.checked_sub_days(Days::new(count))
+        } else {
+            date.0.checked_add_days(Days::new(count))
+        }
+        .ok_or_else(error)?;
+
+        let step = |d: NaiveDate| if days < 0 { d.pred_opt() } else { d.succ_opt() };
+        let mut date = date.0;
+        for _ in 0..count {
+            date = step(date).ok_or_else(error)?;
+            while !self.is_business(date) {
+                date = step(date).ok_or_else(error)?;
+            }
+        }
+        Ok(Value(date))
+    }
+
+    /// The number of business days after `start` up to and including `end`,
+    /// or the negative of the count from `end` to `start` if `end` is earlier.
+    pub fn business_days_between<L: DateLocale>(
+        &self,
+        start: &Value<LocalDate<L>>,
+        end: &Value<LocalDate<L>>,
+    ) -> i64 {
+        let (first, last, sign) = if start.0 <= end.0 {
+            (start.0, end.0, 1)
+        } else {
+            (end.0, start.0, -1)
+        };
+        let count = first
+            .iter_days()
+            .skip(1)
+            .take_while(|d| *d <= last)
+            .filter(|d| self.is_business(*d))
+            .count() as i64;
+        sign * count
+    }
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+fn next_weekday(date: NaiveDate, weekday: Weekday) -> NaiveDate {
+    date.iter_days().find(|d| d.weekday() == weekday).unwrap()
+}
+
+/// The date of Easter Sunday in the Gregorian calendar.
+pub fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    ymd(year, month as u32, day as u32)
+}
+
+// Compute the public holidays for a year.  Each rule gives a date, a name
+// and whether a substitute weekday is observed when the date falls on a weekend.
+fn public_holidays(year: i32, state: Option<State>) -> BTreeMap<NaiveDate, String> {
+    use State::*;
+    use Weekday::*;
+    let in_state = |states: &[State]| state.is_some_and(|s| states.contains(&s));
+    let easter = easter(year);
+    let days = |n: i64| {
+        if n < 0 {
+            easter - Days::new(n.unsigned_abs())
+        } else {
+            easter + Days::new(n as u64)
+        }
+    };
+
+    let mut rules = vec![
+        (ymd(year, 1, 1), "New Year's Day", true),
+        (ymd(year, 1, 26), "Australia Day", true),
+        (days(-2), "Good Friday", false),
+        (days(1), "Easter Monday", false),
+        (ymd(year, 4, 25), "Anzac Day", in_state(&[Act, Wa])),
+        (ymd(year, 12, 25), "Christmas Day", true),
+        (ymd(year, 12, 26), "Boxing Day", true),
+    ];
+
+    if in_state(&[Act, Nsw, Nt, Qld, Sa, Vic]) {
+        rules.push((days(-1), "Easter Saturday", false));
+    }
+    if in_state(&[Act, Nsw, Qld, Vic]) {
+        rules.push((easter, "Easter Sunday", false));
+    }
+    match state {
+        Some(Qld) => rules.push((nth_weekday(year, 10, Mon, 1), "King's Birthday", false)),
+        Some(Wa) => rules.push((last_weekday(year, 9, Mon), "King's Birthday", false)),
+        Some(_) => rules.push((nth_weekday(year, 6, Mon, 2), "King's Birthday", false)),
+        None => {}
+    }
+    match state {
+        Some(Act | Nsw | Sa) => rules.push((nth_weekday(year, 10, Mon, 1), "Labour Day", false)),
+        Some(Vic) => rules.push((nth_weekday(year, 3, Mon, 2), "Labour Day", false)),
+        Some(Tas) => rules.push((nth_weekday(year, 3, Mon, 2), "Eight Hours Day", false)),
+        Some(Wa) => rules.push((nth_weekday(year, 3, Mon, 1), "Labour Day", false)),
+        Some(Qld) => rules.push((nth_weekday(year, 5, Mon, 1), "Labour Day", false)),
+        Some(Nt) => rules.push((nth_weekday(year, 5, Mon, 1), "May Day", false)),
+        None => {}
+    }
+    match state {
+        Some(Act) => {
+            rules.push((nth_weekday(year, 3, Mon, 2), "Canberra Day", false));
+            rules.push((
+                next_weekday(ymd(year, 5, 27), Mon),
+                "Reconciliation Day",
+                false,
+            ));
+        }
+        Some(Sa) => rules.push((nth_weekday(year, 3, Mon, 2), "Adelaide Cup Day", false)),
+        Some(Vic) => rules.push((nth_weekday(year, 11, Tue, 1), "Melbourne Cup Day", false)),
+        Some(Wa) => rules.push((nth_weekday(year, 6, Mon, 1), "Western Australia Day", false)),
+        Some(Nt) => rules.push((nth_weekday(year, 8, Mon, 1), "Picnic Day", false)),
+        _ => {}
+    }
+
+    rules.sort_by_key(|(date, _, _)| *date);
+    let mut holidays: BTreeMap<NaiveDate, String> = BTreeMap::new();
+    let mut substitutes = Vec::new();
+    for (date, name, substitute) in rules {
+        holidays
+            .entry(date)
+            .and_modify(|names| *names = format!("{names} / {name}"))
+            .or_insert_with(|| name.to_string());
+        if substitute && is_weekend(date) {
+            substitutes.push((date, name));
+        }
+    }
+    // A substitute is the next weekday that is not already a holiday,
+    // so that Christmas and Boxing Day on a weekend give the following Monday and Tuesday.
+    for (date, name) in substitutes {
+        let observed = date
+            .iter_days()
+            .find(|d| !is_weekend(*d) && !holidays.contains_key(d));
+        if let Some(observed) = observed {
+            holidays.insert(observed, format!("{name} (substitute)"));
+        }
+    }
+    holidays
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(text: &str) -> Value<Date> {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn holiday_rules() {
+        assert_eq!(easter(2024), ymd(2024, 3, 31));
+        assert_eq!(easter(2025), ymd(2025, 4, 20));
+
+        let nsw = Calendar::state(State::Nsw);
+        let holidays = nsw.holidays(2021);
+        assert_eq!(holidays[&ymd(2021, 12, 27)], "Christmas Day (substitute)");
+        assert_eq!(holidays[&ymd(2021, 12, 28)], "Boxing Day (substitute)");
+        assert_eq!(holidays[&ymd(2021, 6, 14)], "King's Birthday");
+        assert_eq!(holidays[&ymd(2021, 10, 4)], "Labour Day");
+
+        let qld = Calendar::state(State::Qld);
+        assert!(qld.is_business_day(&date("14/06/2021")));
+        assert!(!qld.is_business_day(&date("04/10/2021")));
+        assert!(!Calendar::state(State::Vic).is_business_day(&date("07/11/2023")));
+        assert!(Calendar::state(State::Vic).is_business_day(&date("26/04/2021")));
+        assert!(!Calendar::state(State::Wa).is_business_day(&date("26/04/2021")));
+        assert!(!Calendar::national().is_business_day(&date("06/04/2024")));
+
+        // Anzac Day is Easter Sunday in 2038 and Easter Monday in 2011.
+        let act = Calendar::state(State::Act).holidays(2038);
+        assert_eq!(act[&ymd(2038, 4, 25)], "Anzac Day / Easter Sunday");
+        assert_eq!(act[&ymd(2038, 4, 27)], "Anzac Day (substitute)");
+        let nsw = Calendar::state(State::Nsw).holidays(2011);
+        assert_eq!(nsw[&ymd(2011, 4, 25)], "Easter Monday / Anzac Day");
+    }
+
+    #[test]
+    fn business_day_arithmetic() {
+        let calendar = Calendar::state(State::Nsw)
+            .with_holidays("# proclaimed\n2024-04-02, Extra Day\n")
+            .unwrap();
+        let lodged = date("28/03/2024");
+        let add = |from: &Value<Date>, days| calendar.add_business_days(from, days);
+        assert_eq!(add(&lodged, 1).unwrap(), date("03/04/2024"));
+        assert_eq!(add(&lodged, 0).unwrap(), lodged);
+        assert_eq!(add(&date("03/04/2024"), -2).unwrap(), date("27/03/2024"));
+        assert!(add(&lodged, i64::MAX).is_err());
+        assert!(add(&lodged, i64::MIN).is_err());
+        assert_eq!(
+            calendar.business_days_between(&lodged, &date("05/04/2024")),
+            3
+        );
+        assert_eq!(
+            calendar.business_days_between(&date("05/04/2024"), &lodged),
+            -3
+        );
+        assert!(Calendar::national().with_holidays("tomorrow").is_err());
+    }
+}
@@ -0,0 +1,283 @@
+use super::{
+    money::{money_from_str, money_to_code_str, money_to_str},
+    Quantity, Value,
+};
+use crate::variant::Error;
+
+/// The ISO 4217 particulars of a currency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrencyInfo {
+    /// The three letter alphabetic code e.g. `AUD`
+    pub code: &'static str,
+    /// The numeric code e.g. `36`
+    pub numeric: u16,
+    /// The number of digits in the minor unit e.g. `2` for cents
+    pub precision: u32,
+    /// The symbol used for formatting. This is the code if there is no distinctive symbol.
+    pub symbol: &'static str,
+    /// The English name of the currency
+    pub name: &'static str,
+}
+
+/// A `Currency` is a money `Quantity` represented by an amount in minor units.
+///
+/// A type is defined for each ISO 4217 currency and these are listed in `ISO_4217`.
+/// Values are formatted with the currency symbol by `Quantity::format`
+/// or with the currency code by `Value::to_code_string`.  Either is accepted by `Quantity::parse`,
+/// which rejects amounts with more fraction digits than the currency precision.
+/// See `money::parse_money` to round such amounts instead.
+pub trait Currency: Quantity<Repr = i64> {
+    const INFO: CurrencyInfo;
+}
+
+impl<C: Currency> Value<C> {
+    /// Format with the currency code instead of the symbol e.g. `AUD 12.34`
+    pub fn to_code_string(&self) -> String {
+        money_to_code_str(self.0, &C::INFO)
+    }
+}
+
+/// Find a currency by its alphabetic code.
+pub fn lookup(code: &str) -> Option<&'static CurrencyInfo> {
+    ISO_4217.iter().find(|c| c.code.eq_ignore_ascii_case(code))
+}
+
+/// Find a currency by its numeric code.
+pub fn lookup_numeric(numeric: u16) -> Option<&'static CurrencyInfo> {
+    ISO_4217.iter().find(|c| c.numeric == numeric)
+}
+
+macro_rules! iso_4217 {
+    ($($code:ident $numeric:literal $precision:literal $symbol:literal $name:literal;)*) => {
+        $(
+            #[doc = concat!($name, " (ISO 4217 `", stringify!($code), "`).")]
+            #[derive(Debug, Clone, Copy)]
+            pub struct $code;
+
+            impl Currency for $code {
+                const INFO: CurrencyInfo = CurrencyInfo {
+                    code: stringify!($code),
+                    numeric: $numeric,
+                    precision: $precision,
+                    symbol: $symbol,
+                    name: $name,
+                };
+            }
+
+            impl Quantity for $code {
+                type Repr = i64;
+
+                fn parse(text: &str) -> Result<Self::Repr, Error> {
+                    money_from_str(text, &Self::INFO, None)
+                }
+
+                fn format(value: &Self::Repr) -> String {
+                    money_to_str(*value, &Self::INFO)
+                }
+            }
+        )*
+
+        /// The active ISO 4217 currencies.
+        pub static ISO_4217: &[CurrencyInfo] = &[$($code::INFO),*];
+    };
+}
+
+iso_4217! {
+    AED 784 2 "AED" "UAE Dirham";
+    AFN 971 2 "AFN" "Afghani";
+    ALL 8 2 "ALL" "Lek";
+    AMD 51 2 "AMD" "Armenian Dram";
+    AOA 973 2 "AOA" "Kwanza";
+    ARS 32 2 "ARS" "Argentine Peso";
+    AUD 36 2 "$" "Australian Dollar";
+    AWG 533 2 "AWG" "Aruban Florin";
+    AZN 944 2 "AZN" "Azerbaijan Manat";
+    BAM 977 2 "BAM" "Convertible Mark";
+    BBD 52 2 "BBD" "Barbados Dollar";
+    BDT 50 2 "BDT" "Taka";
+    BGN 975 2 "BGN" "Bulgarian Lev";
+    BHD 48 3 "BHD" "Bahraini Dinar";
+    BIF 108 0 "BIF" "Burundi Franc";
+    BMD 60 2 "BMD" "Bermudian Dollar";
+    BND 96 2 "BND" "Brunei Dollar";
+    BOB 68 2 "BOB" "Boliviano";
+    BRL 986 2 "R$" "Brazilian Real";
+    BSD 44 2 "BSD" "Bahamian Dollar";
+    BTN 64 2 "BTN" "Ngultrum";
+    BWP 72 2 "BWP" "Pula";
+    BYN 933 2 "BYN" "Belarusian Ruble";
+    BZD 84 2 "BZD" "Belize Dollar";
+    CAD 124 2 "CA$" "Canadian Dollar";
+    CDF 976 2 "CDF" "Congolese Franc";
+    CHF 756 2 "CHF" "Swiss Franc";
+    CLF 990 4 "CLF" "Unidad de Fomento";
+    CLP 152 0 "CLP" "Chilean Peso";
+    CNY 156 2 "CN¥" "Yuan Renminbi";
+    COP 170 2 "COP" "Colombian Peso";
+    CRC 188 2 "CRC" "Costa Rican Colon";
+    CUP 192 2 "CUP" "Cuban Peso";
+    CVE 132 2 "CVE" "Cabo Verde Escudo";
+    CZK 203 2 "CZK" "Czech Koruna";
+    DJF 262 0 "DJF" "Djibouti Franc";
+    DKK 208 2 "DKK" "Danish Krone";
+    DOP 214 2 "DOP" "Dominican Peso";
+    DZD 12 2 "DZD" "Algerian Dinar";
+    EGP 818 2 "EGP" "Egyptian Pound";
+    ERN 232 2 "ERN" "Nakfa";
+    ETB 230 2 "ETB" "Ethiopian Birr";
+    EUR 978 2 "€" "Euro";
+    FJD 242 2 "FJD" "Fiji Dollar";
+    FKP 238 2 "FKP" "Falkland Islands Pound";
+    GBP 826 2 "£" "Pound Sterling";
+    GEL 981 2 "GEL" "Lari";
+    GHS 936 2 "GHS" "Ghana Cedi";
+    GIP 292 2 "GIP" "Gibraltar Pound";
+    GMD 270 2 "GMD" "Dalasi";
+    GNF 324 0 "GNF" "Guinean Franc";
+    GTQ 320 2 "GTQ" "Quetzal";
+    GYD 328 2 "GYD" "Guyana Dollar";
+    HKD 344 2 "HK$" "Hong Kong Dollar";
+    HNL 340 2 "HNL" "Lempira";
+    HTG 332 2 "HTG" "Gourde";
+    HUF 348 2 "HUF" "Forint";
+    IDR 360 2 "IDR" "Rupiah";
+    ILS 376 2 "₪" "New Israeli Sheqel";
+    INR 356 2 "₹" "Indian Rupee";
+    IQD 368 3 "IQD" "Iraqi Dinar";
+    IRR 364 2 "IRR" "Iranian Rial";
+    ISK 352 0 "ISK" "Iceland Krona";
+    JMD 388 2 "JMD" "Jamaican Dollar";
+    JOD 400 3 "JOD" "Jordanian Dinar";
+    JPY 392 0 "¥" "Yen";
+    KES 404 2 "KES" "Kenyan Shilling";
+    KGS 417 2 "KGS" "Som";
+    KHR 116 2 "KHR" "Riel";
+    KMF 174 0 "KMF" "Comorian Franc";
+    KPW 408 2 "KPW" "North Korean Won";
+    KRW 410 0 "₩" "Won";
+    KWD 414 3 "KWD" "Kuwaiti Dinar";
+    KYD 136 2 "KYD" "Cayman Islands Dollar";
+    KZT 398 2 "KZT" "Tenge";
+    LAK 418 2 "LAK" "Lao Kip";
+    LBP 422 2 "LBP" "Lebanese Pound";
+    LKR 144 2 "LKR" "Sri Lanka Rupee";
+    LRD 430 2 "LRD" "Liberian Dollar";
+    LSL 426 2 "LSL" "Loti";
+    LYD 434 3 "LYD" "Libyan Dinar";
+    MAD 504 2 "MAD" "Moroccan Dirham";
+    MDL 498 2 "MDL" "Moldovan Leu";
+    MGA 969 2 "MGA" "Malagasy Ariary";
+    MKD 807 2 "MKD" "Denar";
+    MMK 104 2 "MMK" "Kyat";
+    MNT 496 2 "MNT" "Tugrik";
+    MOP 446 2 "MOP" "Pataca";
+    MRU 929 2 "MRU" "Ouguiya";
+    MUR 480 2 "MUR" "Mauritius Rupee";
+    MVR 462 2 "MVR" "Rufiyaa";
+    MWK 454 2 "MWK" "Malawi Kwacha";
+    MXN 484 2 "MX$" "Mexican Peso";
+    MYR 458 2 "MYR" "Malaysian Ringgit";
+    MZN 943 2 "MZN" "Mozambique Metical";
+    NAD 516 2 "NAD" "Namibia Dollar";
+    NGN 566 2 "₦" "Naira";
+    NIO 558 2 "NIO" "Cordoba Oro";
+    NOK 578 2 "NOK" "Norwegian Krone";
+    NPR 524 2 "NPR" "Nepalese Rupee";
+    NZD 554 2 "NZ$" "New Zealand Dollar";
+    OMR 512 3 "OMR" "Rial Omani";
+    PAB 590 2 "PAB" "Balboa";
+    PEN 604 2 "PEN" "Sol";
+    PGK 598 2 "PGK" "Kina";
+    PHP 608 2 "₱" "Philippine Peso";
+    PKR 586 2 "PKR" "Pakistan Rupee";
+    PLN 985 2 "PLN" "Zloty";
+    PYG 600 0 "PYG" "Guarani";
+    QAR 634 2 "QAR" "Qatari Rial";
+    RON 946 2 "RON" "Romanian Leu";
+    RSD 941 2 "RSD" "Serbian Dinar";
+    RUB 643 2 "₽" "Russian Ruble";
+    RWF 646 0 "RWF" "Rwanda Franc";
+    SAR 682 2 "SAR" "Saudi Riyal";
+    SBD 90 2 "SBD" "Solomon Islands Dollar";
+    SCR 690 2 "SCR" "Seychelles Rupee";
+    SDG 938 2 "SDG" "Sudanese Pound";
+    SEK 752 2 "SEK" "Swedish Krona";
+    SGD 702 2 "S$" "Singapore Dollar";
+    SHP 654 2 "SHP" "Saint Helena Pound";
+    SLE 925 2 "SLE" "Leone";
+    SOS 706 2 "SOS" "Somali Shilling";
+    SRD 968 2 "SRD" "Surinam Dollar";
+    SSP 728 2 "SSP" "South Sudanese Pound";
+    STN 930 2 "STN" "Dobra";
+    SVC 222 2 "SVC" "El Salvador Colon";
+    SYP 760 2 "SYP" "Syrian Pound";
+    SZL 748 2 "SZL" "Lilangeni";
+    THB 764 2 "฿" "Baht";
+    TJS 972 2 "TJS" "Somoni";
+    TMT 934 2 "TMT" "Turkmenistan New Manat";
+    TND 788 3 "TND" "Tunisian Dinar";
+    TOP 776 2 "TOP" "Pa'anga";
+    TRY 949 2 "₺" "Turkish Lira";
+    TTD 780 2 "TTD" "Trinidad and Tobago Dollar";
+    TWD 901 2 "NT$" "New Taiwan Dollar";
+    TZS 834 2 "TZS" "Tanzanian Shilling";
+    UAH 980 2 "₴" "Hryvnia";
+    UGX 800 0 "UGX" "Uganda Shilling";
+    USD 840 2 "US$" "US Dollar";
+    UYU 858 2 "UYU" "Peso Uruguayo";
+    UYW 927 4 "UYW" "Unidad Previsional";
+    UZS 860 2 "UZS" "Uzbekistan Sum";
+    VED 926 2 "VED" "Bolívar Soberano (digital)";
+    VES 928 2 "VES" "Bolívar Soberano";
+    VND 704 0 "₫" "Dong";
+    VUV 548 0 "VUV" "Vatu";
+    WST 882 2 "WST" "Tala";
+    XAF 950 0 "XAF" "CFA Franc BEAC";
+    XCD 951 2 "EC$" "East Caribbean Dollar";
+    XCG 532 2 "XCG" "Caribbean Guilder";
+    XOF 952 0 "XOF" "CFA Franc BCEAO";
+    XPF 953 0 "XPF" "CFP Franc";
+    YER 886 2 "YER" "Yemeni Rial";
+    ZAR 710 2 "ZAR" "Rand";
+    ZMW 967 2 "ZMW" "Zambian Kwacha";
+    ZWG 924 2 "ZWG" "Zimbabwe Gold";
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn currency_registry() {
+        assert_eq!(lookup("jpy").unwrap().precision, 0);
+        assert_eq!(lookup("BHD").unwrap().precision, 3);
+        assert_eq!(lookup_numeric(36).unwrap().code, "AUD");
+        assert!(lookup("XYZ").is_none());
+        for (i, a) in ISO_4217.iter().enumerate() {
+            for b in &ISO_4217[i + 1..] {
+                assert!(a.code != b.code && a.numeric != b.numeric, "{}", a.code);
+            }
+        }
+    }
+
+    #[test]
+    fn currency_precision_and_symbols() {
+        assert_eq!(Value::<JPY>::from_repr(1234).to_string(), "¥1234");
+        assert_eq!(Value::<BHD>::from_repr(12345).to_string(), "BHD 12.345");
+        assert_eq!(Value::<USD>::from_repr(1234).to_string(), "US$12.34");
+        assert_eq!(Value::<AUD>::from_repr(1234).to_code_string(), "AUD 12.34");
+        assert_eq!(Value::<AUD>::from_repr(-5).to_code_string(), "-AUD 0.05");
+        assert_eq!(
+            Value::<AUD>::from_repr(1234),
+            "AUD 12.34".parse::<Value<AUD>>().unwrap()
+        );
+        assert_eq!(
+            Value::<BHD>::from_repr(-12345),
+            "-BHD 12.345".parse::<Value<BHD>>().unwrap()
+        );
+        assert_eq!(
+            Value::<JPY>::from_repr(500),
+            "¥500".parse::<Value<JPY>>().unwrap()
+        );
+    }
+}
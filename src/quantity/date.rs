@@ -1,31 +1,174 @@
 use super::Quantity;
-use chrono::{Datelike, NaiveDate};
+use crate::variant::Error;
+use chrono::NaiveDate;
+use std::marker::PhantomData;
 
 /// A naive date quantity with opinionated formatting and parsing.
-pub struct Date;
+///
+/// Dates are written day first e.g. `23/05/2001`.  See `LocalDate` for other conventions.
+pub type Date = LocalDate<Au>;
 
-static FORMATS: &[&str] = &["%d/%m/%Y", "%F", "%v", "%d.%m.%Y"];
+/// A naive date quantity whose formatting and parsing are determined by a `DateLocale`.
+pub struct LocalDate<L>(PhantomData<L>);
 
-impl Quantity for Date {
+/// A convention for writing dates.
+pub trait DateLocale {
+    const PARSER: DateParser;
+}
+
+/// Dates written day first as in Australia e.g. `23/05/2001`.
+pub struct Au;
+
+/// Dates written month first as in the United States e.g. `05/23/2001`.
+pub struct Us;
+
+/// Dates written in ISO 8601 form e.g. `2001-05-23`.
+///
+/// Other numeric dates are accepted when the order of day and month is evident.
+pub struct Iso;
+
+impl DateLocale for Au {
+    const PARSER: DateParser = DateParser::new(DateOrder::DayFirst, "%d/%m/%Y");
+}
+
+impl DateLocale for Us {
+    const PARSER: DateParser = DateParser::new(DateOrder::MonthFirst, "%m/%d/%Y");
+}
+
+impl DateLocale for Iso {
+    const PARSER: DateParser = DateParser::new(DateOrder::Detect, "%F");
+}
+
+impl<L: DateLocale> Quantity for LocalDate<L> {
     type Repr = NaiveDate;
 
-    fn parse(text: &str) -> Result<Self::Repr, crate::variant::Error> {
-        for fmt in FORMATS {
-            if let Ok(d) = NaiveDate::parse_from_str(text, fmt) {
-                let d = if d.year() < 100 {
-                    NaiveDate::from_ymd_opt(d.year() + 2000, d.month(), d.day()).unwrap()
-                } else {
-                    d
+    fn parse(text: &str) -> Result<Self::Repr, Error> {
+        L::PARSER.parse(text)
+    }
+
+    fn format(value: &Self::Repr) -> String {
+        L::PARSER.format(value)
+    }
+}
+
+/// The order of day and month in a numeric date such as `05/06/2001`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    DayFirst,
+    MonthFirst,
+    /// Infer the order from the values, which is an error when either order is valid.
+    Detect,
+}
+
+/// Parses and formats dates according to a convention.
+///
+/// A date is three components separated by `/`, `.`, `-`, `,` or spaces.
+/// A year written first with four digits gives year-month-day order.
+/// A month may be written as a name or abbreviation e.g. `23 May 2001` or `May 23, 2001`.
+/// Otherwise the order of day and month is given by `DateOrder`.
+///
+/// A two digit year is in this century if less than the pivot, otherwise the last century.
+#[derive(Debug, Clone, Copy)]
+pub struct DateParser {
+    pub order: DateOrder,
+    pub pivot: i32,
+    pub format: &'static str,
+}
+
+static MONTHS: &[&str] = &[
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+impl DateParser {
+    /// A parser with the given order and output format and a two digit year pivot of 50.
+    pub const fn new(order: DateOrder, format: &'static str) -> Self {
+        Self {
+            order,
+            pivot: 50,
+            format,
+        }
+    }
+
+    /// Change the two digit year pivot.
+    pub const fn with_pivot(self, pivot: i32) -> Self {
+        Self { pivot, ..self }
+    }
+
+    pub fn format(&self, date: &NaiveDate) -> String {
+        date.format(self.format).to_string()
+    }
+
+    pub fn parse(&self, text: &str) -> Result<NaiveDate, Error> {
+        let error = |reason: &str| Error::Detail(format!("{reason} in date '{}'", text.trim()));
+        let parts: Vec<&str> = text
+            .split(|c: char| "/.-,".contains(c) || c.is_whitespace())
+            .filter(|s| !s.is_empty())
+            .collect();
+        let [a, b, c] = parts[..] else {
+            Err(error("expected day, month and year"))?
+        };
+
+        let (year, month, day) = match (month_name(a), month_name(b)) {
+            (Some(m), _) => (c, m, b),
+            (_, Some(m)) => (c, m, a),
+            _ if a.len() == 4 => (a, self.number(b)?, c),
+            _ => {
+                let (x, y) = (self.number(a)?, self.number(b)?);
+                let day_first = match self.order {
+                    DateOrder::DayFirst => true,
+                    DateOrder::MonthFirst => false,
+                    DateOrder::Detect if x == y || y > 12 => false,
+                    DateOrder::Detect if x > 12 => true,
+                    DateOrder::Detect => Err(error("ambiguous day and month"))?,
                 };
-                return Ok(d);
+                if day_first {
+                    (c, y, a)
+                } else {
+                    (c, x, b)
+                }
             }
+        };
+
+        let year = match (year.len(), self.number(year)) {
+            (2, Ok(y)) if (y as i32) < self.pivot => 2000 + y as i32,
+            (2, Ok(y)) => 1900 + y as i32,
+            (4, Ok(y)) => y as i32,
+            _ => Err(error("invalid year"))?,
+        };
+        let day = self.number(day.trim_end_matches(['s', 't', 'n', 'd', 'r', 'h']))?;
+        NaiveDate::from_ymd_opt(year, month, day).ok_or_else(|| error("invalid day or month"))
+    }
+
+    fn number(&self, text: &str) -> Result<u32, Error> {
+        if text.is_empty() || text.len() > 4 || !text.chars().all(|c| c.is_ascii_digit()) {
+            Err(Error::Detail(format!(
+                "expected a number in date, got '{text}'"
+            )))?
         }
-        Err("unrecognised date format".into())
+        Ok(text.parse().unwrap())
     }
+}
 
-    fn format(value: &Self::Repr) -> String {
-        value.format("%d/%m/%Y").to_string()
+fn month_name(text: &str) -> Option<u32> {
+    let text = text.to_lowercase();
+    if text.len() < 3 {
+        return None;
     }
+    MONTHS
+        .iter()
+        .position(|m| m.starts_with(&text) || (text == "sept" && *m == "september"))
+        .map(|i| i as u32 + 1)
 }
 
 #[cfg(test)]
@@ -53,4 +196,52 @@ mod test {
             "2001-05-23".parse::<C>().unwrap()
         );
     }
+
+    #[test]
+    fn date_locales_and_pivot() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        type A = Value<Date>;
+        type U = Value<LocalDate<Us>>;
+        type I = Value<LocalDate<Iso>>;
+
+        assert_eq!(
+            "23/05/95".parse::<A>().unwrap().to_repr(),
+            date(1995, 5, 23)
+        );
+        assert_eq!(
+            "05/06/2001".parse::<A>().unwrap().to_repr(),
+            date(2001, 6, 5)
+        );
+        assert!("05/23/2001".parse::<A>().is_err());
+        assert_eq!(
+            "05/23/2001".parse::<U>().unwrap().to_repr(),
+            date(2001, 5, 23)
+        );
+        assert_eq!(
+            "05/06/2001".parse::<U>().unwrap().to_repr(),
+            date(2001, 5, 6)
+        );
+        assert_eq!(U::from_repr(date(2001, 5, 23)).to_string(), "05/23/2001");
+
+        assert_eq!(
+            "05/23/2001".parse::<I>().unwrap().to_repr(),
+            date(2001, 5, 23)
+        );
+        assert_eq!(
+            "23/05/2001".parse::<I>().unwrap().to_repr(),
+            date(2001, 5, 23)
+        );
+        assert!("05/06/2001".parse::<I>().is_err());
+        assert_eq!(I::from_repr(date(2001, 5, 23)).to_string(), "2001-05-23");
+
+        for text in ["23 May 2001", "May 23, 2001", "23-may-01", "23rd Sept 2001"] {
+            let month = if text.contains("Sept") { 9 } else { 5 };
+            assert_eq!(text.parse::<I>().unwrap().to_repr(), date(2001, month, 23));
+        }
+        assert!("23 Mai 2001".parse::<A>().is_err());
+
+        let parser = Au::PARSER.with_pivot(30);
+        assert_eq!(parser.parse("1/1/29").unwrap(), date(2029, 1, 1));
+        assert_eq!(parser.parse("1/1/30").unwrap(), date(1930, 1, 1));
+    }
 }
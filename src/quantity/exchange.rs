@@ -0,0 +1,368 @@
+use super::{
+    currency::{Currency, CurrencyInfo},
+    date::Date,
+    rounding::{decimal_ratio, scale, Rounding},
+    Value,
+};
+use crate::{
+    propagator::{Propagator, Propagators},
+    property::{Path, Property},
+    table::{Ident, IdentPath, Table},
+    variant::{Error, Variant},
+};
+use chrono::NaiveDate;
+use std::{marker::PhantomData, rc::Rc};
+
+/// An exchange rate taken from a rate table.
+///
+/// A rate table is a `Table` of source currency codes to tables of target currency
+/// codes to tables of effective dates (`YYYY-MM-DD`) to rates. So `rates/USD/AUD/2024-07-01`
+/// is the number of Australian dollars per US dollar from 1 July 2024 until the next effective date.
+/// A table can be built with `rate_table`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExchangeRate {
+    pub from: &'static str,
+    pub to: &'static str,
+    pub effective: NaiveDate,
+    /// The rate as found in the table, which is the number of `to` units per `from` unit
+    /// or, if `inverse`, the number of `from` units per `to` unit.
+    pub rate: f64,
+    /// True if the rate was found for the opposite direction and is to be inverted
+    pub inverse: bool,
+}
+
+/// Build a rate table from `(from, to, effective, rate)` entries.
+pub fn rate_table<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a str, NaiveDate, f64)>,
+) -> Table {
+    let mut table = Table::new();
+    for (from, to, effective, rate) in entries {
+        let mut dates = Table::new();
+        dates.join_entry(effective.format("%F").to_string().into(), rate.into());
+        let mut targets = Table::new();
+        targets.join_entry(to.to_string().into(), Rc::new(dates).into());
+        table.join_entry(from.to_string().into(), Rc::new(targets).into());
+    }
+    table
+}
+
+impl ExchangeRate {
+    /// Find the rate in effect on a date.  If there is no rate for the pair
+    /// of currencies, the rate for the opposite direction is inverted.
+    pub fn lookup(
+        rates: &Table,
+        from: &CurrencyInfo,
+        to: &CurrencyInfo,
+        on: NaiveDate,
+    ) -> Result<ExchangeRate, Error> {
+        let found =
+            |a: &CurrencyInfo, b: &CurrencyInfo| -> Result<Option<(NaiveDate, f64)>, Error> {
+                let path = IdentPath::new(Ident::Intern(a.code)).append(Ident::Intern(b.code));
+                let Some(dates) = rates.get_path(&path).and_then(Variant::as_table) else {
+                    return Ok(None);
+                };
+                let mut best = None;
+                for (date, rate) in dates.iter() {
+                    let date = date
+                        .as_str()
+                        .and_then(|d| NaiveDate::parse_from_str(d, "%F").ok())
+                        .ok_or_else(|| {
+                            Error::Detail(format!("invalid effective date {date} in rate table"))
+                        })?;
+                    if date <= on && best.is_none_or(|(d, _)| date > d) {
+                        best = Some((date, rate_value(rate)?));
+                    }
+                }
+                Ok(best)
+            };
+
+        let (effective, rate, inverse) = if let Some((d, r)) = found(from, to)? {
+            (d, r, false)
+        } else if let Some((d, r)) = found(to, from)? {
+            (d, r, true)
+        } else {
+            Err(Error::Detail(format!(
+                "no {}/{} rate effective on {on}",
+                from.code, to.code
+            )))?
+        };
+
+        Ok(ExchangeRate {
+            from: from.code,
+            to: to.code,
+            effective,
+            rate,
+            inverse,
+        })
+    }
+
+    /// Convert an amount with this rate, rounding to the precision of the target currency.
+    pub fn apply<S: Currency, T: Currency>(
+        &self,
+        amount: Value<S>,
+        rounding: Rounding,
+    ) -> Result<Value<T>, Error> {
+        if self.from != S::INFO.code || self.to != T::INFO.code {
+            Err("exchange rate does not match currencies")?
+        }
+        let error = "exchange rate out of range";
+        let (mut numerator, mut denominator) = decimal_ratio(self.rate)?;
+        if self.inverse {
+            (numerator, denominator) = (denominator, numerator);
+        }
+        numerator = numerator
+            .checked_mul(10i128.pow(T::INFO.precision))
+            .ok_or(error)?;
+        denominator = denominator
+            .checked_mul(10i128.pow(S::INFO.precision))
+            .ok_or(error)?;
+        Ok(Value(scale(amount.0, numerator, denominator, rounding)?))
+    }
+
+    /// A record of this rate as a `Table` with entries
+    /// `from`, `to`, `effective`, `rate` and `inverse` (1 or 0).
+    pub fn provenance(&self) -> Table {
+        let mut table = Table::new();
+        table.join_entry(Ident::Intern("from"), self.from.to_string().into());
+        table.join_entry(Ident::Intern("to"), self.to.to_string().into());
+        table.join_entry(Ident::Intern("effective"), self.effective.into());
+        table.join_entry(Ident::Intern("rate"), self.rate.into());
+        table.join_entry(Ident::Intern("inverse"), (self.inverse as i64).into());
+        table
+    }
+}
+
+fn rate_value(value: &Variant) -> Result<f64, Error> {
+    match value {
+        Variant::Float(x) => Ok(*x),
+        Variant::Int(i) => Ok(*i as f64),
+        Variant::String(s) => Ok(s.trim().parse().or(Err("invalid rate in rate table"))?),
+        _ => Err("invalid rate in rate table".into()),
+    }
+}
+
+/// Construct propagators that convert an amount of currency `S` into currency `T`.
+///
+/// The rate is found in the rate table at `rates` for the date at `date`, see `ExchangeRate`.
+/// For example:
+///
+/// `convert(&FEE_AUD, &FEE_USD, &SERVICE_DATE, &RATES).provenance(&FEE_RATE).build()`
+pub fn convert<S, T>(
+    target: &Property<Value<T>>,
+    amount: impl Into<Path<Value<S>>>,
+    date: impl Into<Path<Value<Date>>>,
+    rates: impl Into<Path<Rc<Table>>>,
+) -> Conversion<S, T>
+where
+    S: Currency,
+    T: Currency,
+{
+    Conversion {
+        target: target.name.clone(),
+        provenance: None,
+        inputs: Inputs {
+            amount: amount.into(),
+            date: date.into(),
+            rates: rates.into(),
+            rounding: Rounding::HalfUp,
+            marker: PhantomData,
+        },
+    }
+}
+
+/// A builder for currency conversion propagators. See `convert`.
+pub struct Conversion<S: Currency, T: Currency> {
+    target: Ident,
+    provenance: Option<Ident>,
+    inputs: Inputs<S, T>,
+}
+
+struct Inputs<S: Currency, T: Currency> {
+    amount: Path<Value<S>>,
+    date: Path<Value<Date>>,
+    rates: Path<Rc<Table>>,
+    rounding: Rounding,
+    marker: PhantomData<T>,
+}
+
+impl<S, T> Conversion<S, T>
+where
+    S: Currency + 'static,
+    T: Currency + 'static,
+{
+    /// Round the converted amount with the given rounding instead of half up.
+    pub fn rounding(mut self, rounding: Rounding) -> Self {
+        self.inputs.rounding = rounding;
+        self
+    }
+
+    /// Also record the rate used, as a table described in `ExchangeRate::provenance`.
+    pub fn provenance(mut self, prop: &Property<Rc<Table>>) -> Self {
+        self.provenance = Some(prop.name.clone());
+        self
+    }
+
+    /// Return the conversion propagator and the provenance propagator, if any.
+    pub fn build(self) -> Propagators {
+        let inputs = Rc::new(self.inputs);
+        let mut result: Propagators = vec![Box::new(Converter {
+            target: self.target,
+            inputs: inputs.clone(),
+            output: PhantomData::<Amount>,
+        })];
+        if let Some(target) = self.provenance {
+            result.push(Box::new(Converter {
+                target,
+                inputs,
+                output: PhantomData::<Provenance>,
+            }))
+        }
+        result
+    }
+}
+
+struct Amount;
+struct Provenance;
+
+struct Converter<S: Currency, T: Currency, O> {
+    target: Ident,
+    inputs: Rc<Inputs<S, T>>,
+    output: PhantomData<O>,
+}
+
+impl<S: Currency, T: Currency> Inputs<S, T> {
+    fn convert(&self, state: &Table) -> Option<Result<(Value<T>, ExchangeRate), Error>> {
+        let amount = self.amount.query(state)?;
+        let date = self.date.query(state)?;
+        let rates = self.rates.query(state)?;
+        let result =
+            ExchangeRate::lookup(&rates, &S::INFO, &T::INFO, date.to_repr()).and_then(|rate| {
+                let converted = rate.apply(amount, self.rounding)?;
+                Ok((converted, rate))
+            });
+        Some(result)
+    }
+
+    fn dependencies(&self) -> Vec<&IdentPath> {
+        Vec::from([
+            self.amount.ident_path(),
+            self.date.ident_path(),
+            self.rates.ident_path(),
+        ])
+    }
+}
+
+impl<S: Currency, T: Currency> Propagator for Converter<S, T, Amount> {
+    fn target(&self) -> &Ident {
+        &self.target
+    }
+
+    fn dependencies(&self) -> Vec<&IdentPath> {
+        self.inputs.dependencies()
+    }
+
+    fn fire(&self, state: &Table) -> Option<Variant> {
+        Some(self.inputs.convert(state)?.map(|(amount, _)| amount).into())
+    }
+}
+
+impl<S: Currency, T: Currency> Propagator for Converter<S, T, Provenance> {
+    fn target(&self) -> &Ident {
+        &self.target
+    }
+
+    fn dependencies(&self) -> Vec<&IdentPath> {
+        self.inputs.dependencies()
+    }
+
+    fn fire(&self, state: &Table) -> Option<Variant> {
+        Some(
+            self.inputs
+                .convert(state)?
+                .map(|(_, rate)| Rc::new(rate.provenance()))
+                .into(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        propagator::evaluate_naive,
+        property::prop,
+        quantity::{
+            currency::{AUD, JPY, USD},
+            quant,
+        },
+    };
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn rate_lookup_and_apply() {
+        let rates = rate_table([
+            ("USD", "AUD", date(2024, 7, 1), 1.5),
+            ("USD", "AUD", date(2024, 8, 1), 1.48),
+            ("AUD", "JPY", date(2024, 7, 1), 100.0),
+        ]);
+        let rate = ExchangeRate::lookup(&rates, &USD::INFO, &AUD::INFO, date(2024, 7, 31)).unwrap();
+        assert_eq!(rate.effective, date(2024, 7, 1));
+        let aud: Value<AUD> = rate
+            .apply(Value::<USD>::from_repr(1001), Rounding::HalfUp)
+            .unwrap();
+        assert_eq!(aud, Value::from_repr(1502));
+
+        let rate = ExchangeRate::lookup(&rates, &USD::INFO, &AUD::INFO, date(2024, 8, 1)).unwrap();
+        assert_eq!(rate.rate, 1.48);
+        assert!(ExchangeRate::lookup(&rates, &USD::INFO, &AUD::INFO, date(2024, 6, 30)).is_err());
+
+        let rate = ExchangeRate::lookup(&rates, &AUD::INFO, &JPY::INFO, date(2024, 7, 1)).unwrap();
+        let jpy: Value<JPY> = rate
+            .apply(Value::<AUD>::from_repr(1234), Rounding::HalfUp)
+            .unwrap();
+        assert_eq!(jpy, Value::from_repr(1234));
+
+        let rate = ExchangeRate::lookup(&rates, &JPY::INFO, &AUD::INFO, date(2024, 7, 1)).unwrap();
+        assert!(rate.inverse);
+        let aud: Value<AUD> = rate
+            .apply(Value::<JPY>::from_repr(1234), Rounding::HalfUp)
+            .unwrap();
+        assert_eq!(aud, Value::from_repr(1234));
+    }
+
+    #[test]
+    fn conversion_propagators() {
+        static FEE_USD: Property<Value<USD>> = quant("fee_usd");
+        static FEE_AUD: Property<Value<AUD>> = quant("fee_aud");
+        static SERVICE_DATE: Property<Value<Date>> = quant("service_date");
+        static RATES: Property<Rc<Table>> = prop("rates");
+        static FEE_RATE: Property<Rc<Table>> = prop("fee_rate");
+
+        let rules = convert(&FEE_AUD, &FEE_USD, &SERVICE_DATE, &RATES)
+            .provenance(&FEE_RATE)
+            .build();
+
+        let mut state = Table::new();
+        let rates = rate_table([("USD", "AUD", date(2024, 7, 1), 1.5)]);
+        state.join_entry(RATES.name.clone(), Rc::new(rates).into());
+        state.join_entry(FEE_USD.name.clone(), Value::<USD>::from_repr(1000).into());
+        state.join_entry(SERVICE_DATE.name.clone(), date(2024, 7, 2).into());
+        evaluate_naive(&mut state, &rules, 10).unwrap();
+
+        let fee: Value<AUD> = state
+            .get(&FEE_AUD.name)
+            .unwrap()
+            .clone()
+            .try_into()
+            .unwrap();
+        assert_eq!(fee, Value::from_repr(1500));
+        let record = state.get(&FEE_RATE.name).unwrap().as_table().unwrap();
+        assert!(matches!(record.get(&Ident::Intern("rate")), Some(Variant::Float(r)) if *r == 1.5));
+        assert!(
+            matches!(record.get(&Ident::Intern("effective")), Some(Variant::Date(d)) if *d == date(2024, 7, 1))
+        );
+    }
+}
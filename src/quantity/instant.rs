@@ -0,0 +1,284 @@
+use super::{
+    date::{Au, DateLocale, DateParser, Iso, LocalDate},
+    Quantity, Value,
+};
+use crate::variant::Error;
+use chrono::{
+    DateTime, FixedOffset, LocalResult, NaiveDate, NaiveTime, SecondsFormat, TimeZone, Utc,
+};
+use std::marker::PhantomData;
+
+pub use chrono_tz::Tz;
+
+/// An instant in time written in UTC e.g. `2024-07-01T00:30:00Z`.  See `ZonedInstant` for local time.
+pub type Instant = ZonedInstant<Universal>;
+
+/// An instant in time whose parsing and formatting are in the local time of a `Zone`.
+///
+/// The representation is always UTC so instants in different zones compare and join as equal.
+pub struct ZonedInstant<Z>(PhantomData<Z>);
+
+/// A time zone and a convention for writing the dates of instants.
+pub trait Zone {
+    const PARSER: InstantParser;
+}
+
+/// Coordinated universal time with ISO 8601 dates.
+pub struct Universal;
+
+/// Sydney and Canberra time, with daylight saving.
+pub struct Sydney;
+
+/// Melbourne time, with daylight saving.
+pub struct Melbourne;
+
+/// Brisbane time, without daylight saving.
+pub struct Brisbane;
+
+/// Adelaide time, with daylight saving.
+pub struct Adelaide;
+
+/// Perth time, without daylight saving.
+pub struct Perth;
+
+/// Hobart time, with daylight saving.
+pub struct Hobart;
+
+/// Darwin time, without daylight saving.
+pub struct Darwin;
+
+impl Zone for Universal {
+    const PARSER: InstantParser = InstantParser::new(Tz::UTC, Iso::PARSER);
+}
+
+impl Zone for Sydney {
+    const PARSER: InstantParser = InstantParser::new(Tz::Australia__Sydney, Au::PARSER);
+}
+
+impl Zone for Melbourne {
+    const PARSER: InstantParser = InstantParser::new(Tz::Australia__Melbourne, Au::PARSER);
+}
+
+impl Zone for Brisbane {
+    const PARSER: InstantParser = InstantParser::new(Tz::Australia__Brisbane, Au::PARSER);
+}
+
+impl Zone for Adelaide {
+    const PARSER: InstantParser = InstantParser::new(Tz::Australia__Adelaide, Au::PARSER);
+}
+
+impl Zone for Perth {
+    const PARSER: InstantParser = InstantParser::new(Tz::Australia__Perth, Au::PARSER);
+}
+
+impl Zone for Hobart {
+    const PARSER: InstantParser = InstantParser::new(Tz::Australia__Hobart, Au::PARSER);
+}
+
+impl Zone for Darwin {
+    const PARSER: InstantParser = InstantParser::new(Tz::Australia__Darwin, Au::PARSER);
+}
+
+impl<Z: Zone> Quantity for ZonedInstant<Z> {
+    type Repr = DateTime<Utc>;
+
+    fn parse(text: &str) -> Result<Self::Repr, Error> {
+        Z::PARSER.parse(text)
+    }
+
+    fn format(value: &Self::Repr) -> String {
+        Z::PARSER.format(value)
+    }
+}
+
+impl<Z: Zone> Value<ZonedInstant<Z>> {
+    /// The first instant of a date in the zone.
+    pub fn start_of_day<L: DateLocale>(date: &Value<LocalDate<L>>) -> Result<Self, Error> {
+        Ok(Self(Z::PARSER.start_of_day(date.0)?))
+    }
+
+    /// The date of this instant in the zone.
+    pub fn date<L: DateLocale>(&self) -> Value<LocalDate<L>> {
+        Value(Z::PARSER.date(&self.0))
+    }
+
+    /// The same instant written in another zone.
+    pub fn in_zone<Y: Zone>(&self) -> Value<ZonedInstant<Y>> {
+        Value(self.0)
+    }
+}
+
+impl<L: DateLocale> Value<LocalDate<L>> {
+    /// The first instant of this date in a zone.
+    pub fn start_in<Z: Zone>(&self) -> Result<Value<ZonedInstant<Z>>, Error> {
+        Value::start_of_day(self)
+    }
+}
+
+/// Parses and formats instants in the local time of a zone.
+///
+/// An instant written with an offset, in RFC 3339 or RFC 2822 form, is exact
+/// e.g. `2024-07-01T09:30:00+10:00` or `Mon, 1 Jul 2024 09:30:00 +1000`.
+/// Otherwise an instant is a date followed by a time, and an optional offset or IANA zone name
+/// e.g. `1/7/2024 9:30am`, `2024-07-01 09:30:00 +10:00` or `2024-07-01T09:30 Australia/Perth`.
+/// The date is read by the `DateParser` and the time is in 24 or 12 hour form.
+/// Without an offset or zone name the time is local to the configured zone.
+///
+/// A local time that is repeated or skipped by a daylight saving change is an error.
+/// An instant is written in RFC 3339 form with the local time and offset of the zone.
+#[derive(Debug, Clone, Copy)]
+pub struct InstantParser {
+    pub zone: Tz,
+    pub dates: DateParser,
+}
+
+static TIMES: &[&str] = &["%H:%M:%S%.f", "%H:%M", "%I:%M:%S%.f%p", "%I:%M%p"];
+
+impl InstantParser {
+    /// A parser for the given zone that reads dates with the given `DateParser`.
+    pub const fn new(zone: Tz, dates: DateParser) -> Self {
+        Self { zone, dates }
+    }
+
+    pub fn format(&self, instant: &DateTime<Utc>) -> String {
+        instant
+            .with_timezone(&self.zone)
+            .to_rfc3339_opts(SecondsFormat::AutoSi, true)
+    }
+
+    pub fn parse(&self, text: &str) -> Result<DateTime<Utc>, Error> {
+        let text = text.trim();
+        let error = |reason: &str| Error::Detail(format!("{reason} in instant '{text}'"));
+        if let Ok(exact) =
+            DateTime::parse_from_rfc3339(text).or_else(|_| DateTime::parse_from_rfc2822(text))
+        {
+            return Ok(exact.with_timezone(&Utc));
+        }
+
+        let (rest, last) = text.rsplit_once(char::is_whitespace).unwrap_or(("", text));
+        let (local, zone, offset) = match (last.parse::<Tz>(), offset(last)) {
+            (Ok(zone), _) => (rest, zone, None),
+            (_, Some(offset)) => (rest, self.zone, Some(offset)),
+            _ => (text, self.zone, None),
+        };
+
+        let (date, time) = match local.split_once('T') {
+            Some((date, time)) if date.ends_with(|c: char| c.is_ascii_digit()) => (date, time),
+            _ => {
+                let colon = local.find(':').ok_or_else(|| error("expected a time"))?;
+                let split = local[..colon]
+                    .rfind(char::is_whitespace)
+                    .ok_or_else(|| error("expected a date"))?;
+                local.split_at(split)
+            }
+        };
+        let date = self.dates.parse(date)?;
+        let time: String = time.split_whitespace().collect();
+        let time = TIMES
+            .iter()
+            .find_map(|format| NaiveTime::parse_from_str(&time, format).ok())
+            .ok_or_else(|| error("invalid time"))?;
+
+        let local = date.and_time(time);
+        let resolved = match offset {
+            Some(offset) => offset
+                .from_local_datetime(&local)
+                .map(|t| t.with_timezone(&Utc)),
+            None => zone
+                .from_local_datetime(&local)
+                .map(|t| t.with_timezone(&Utc)),
+        };
+        match resolved {
+            LocalResult::Single(instant) => Ok(instant),
+            LocalResult::Ambiguous(_, _) => Err(error("local time repeated by daylight saving")),
+            LocalResult::None => Err(error("local time skipped by daylight saving")),
+        }
+    }
+
+    /// The date of an instant in the zone.
+    pub fn date(&self, instant: &DateTime<Utc>) -> NaiveDate {
+        instant.with_timezone(&self.zone).date_naive()
+    }
+
+    /// The first instant of a date in the zone.
+    pub fn start_of_day(&self, date: NaiveDate) -> Result<DateTime<Utc>, Error> {
+        (0..24)
+            .find_map(|hour| {
+                let local = date.and_hms_opt(hour, 0, 0)?;
+                self.zone.from_local_datetime(&local).earliest()
+            })
+            .map(|t| t.with_timezone(&Utc))
+            .ok_or_else(|| Error::Detail(format!("no start of day {date} in {}", self.zone)))
+    }
+}
+
+/// Read an offset such as `Z`, `+10`, `+1000` or `+10:00`.
+fn offset(text: &str) -> Option<FixedOffset> {
+    if text == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+    let sign = match text.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => None?,
+    };
+    let digits: String = text[1..].chars().filter(|c| *c != ':').collect();
+    if !matches!(digits.len(), 2 | 4) || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = digits[2..].parse().unwrap_or(0);
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::quantity::date::Date;
+
+    #[test]
+    fn zoned_instants() {
+        type S = Value<ZonedInstant<Sydney>>;
+        type U = Value<Instant>;
+        let utc = |text: &str| text.parse::<DateTime<Utc>>().unwrap();
+
+        let winter = "1/7/2024 9:30am".parse::<S>().unwrap();
+        assert_eq!(winter.clone().to_repr(), utc("2024-06-30T23:30:00Z"));
+        assert_eq!(winter.to_string(), "2024-07-01T09:30:00+10:00");
+        assert_eq!(
+            winter.in_zone::<Universal>().to_string(),
+            "2024-06-30T23:30:00Z"
+        );
+        let summer = "2024-12-01 09:30".parse::<S>().unwrap();
+        assert_eq!(summer.to_string(), "2024-12-01T09:30:00+11:00");
+
+        for text in [
+            "2024-07-01T09:30:00+10:00",
+            "Mon, 1 Jul 2024 09:30:00 +1000",
+            "2024-07-01 07:30 Australia/Perth",
+            "July 1, 2024 09:30 +10",
+            "2024-06-30 23:30:00 Z",
+        ] {
+            assert_eq!(text.parse::<S>().unwrap(), winter, "{text}");
+        }
+
+        // Daylight saving began 2:00 6 October and ended 3:00 7 April 2024 in Sydney.
+        assert!("6/10/2024 2:30".parse::<S>().is_err());
+        assert!("7/4/2024 2:30".parse::<S>().is_err());
+        assert!("7/4/2024 2:30 +10:00".parse::<S>().is_ok());
+        assert!("2024-07-01".parse::<U>().is_err());
+
+        let late = "2024-06-30T23:30:00Z".parse::<U>().unwrap();
+        assert_eq!(late.date::<Au>().to_string(), "30/06/2024");
+        assert_eq!(
+            late.in_zone::<Sydney>().date::<Au>().to_string(),
+            "01/07/2024"
+        );
+        let date: Value<Date> = "1/7/2024".parse().unwrap();
+        assert_eq!(
+            date.start_in::<Sydney>().unwrap().to_repr(),
+            utc("2024-06-30T14:00:00Z")
+        );
+        assert_eq!(S::start_of_day(&date).unwrap().date::<Au>(), date);
+    }
+}
@@ -1,8 +1,20 @@
 #![cfg(feature = "quantity")]
 
+pub mod calendar;
+pub mod currency;
 pub mod date;
+pub mod exchange;
+pub mod instant;
 pub mod money;
+pub mod period;
+pub mod proportion;
+pub mod rounding;
+pub mod unit;
 
+use self::{
+    proportion::Rate,
+    rounding::{scale, scale_factor, Rounding},
+};
 use crate::{
     property::{prop, Property},
     variant::{Error, Variant},
@@ -10,7 +22,8 @@ use crate::{
 
 use std::{
     fmt::{Debug, Display},
-    ops::{Add, Mul, Neg},
+    iter::Sum,
+    ops::{Add, Div, Mul, Neg, Sub},
     str::FromStr,
 };
 
@@ -46,6 +59,10 @@ pub trait Quantity {
 ///
 /// Essentially a _newtype_ for `Quantity::Repr`.
 /// Blanket implimentations are defined for `TryFrom<Variant>`, `Into<Variant>`, `FromStr` and `Display`.
+///
+/// Values of the same quantity can be added, subtracted, summed and compared, and divided
+/// giving a `Rate`, when the representation allows.  A value can be multiplied or divided by a number.
+/// The `checked_*` methods report overflow as an `Error`.
 pub struct Value<Q: Quantity>(Q::Repr);
 
 /// Construct a Property in a const context.
@@ -59,7 +76,7 @@ pub const fn quant<Q: Quantity>(name: &'static str) -> Property<Value<Q>> {
 
 impl<Q: Quantity> Value<Q> {
     /// Construct a `Value` from its representation.
-    pub fn from_repr(repr: Q::Repr) -> Self {
+    pub const fn from_repr(repr: Q::Repr) -> Self {
         Self(repr)
     }
 
@@ -73,9 +90,210 @@ impl<Q> Value<Q>
 where
     Q: Quantity<Repr = i64>,
 {
-    /// Scale a `Value` that has an i64 representation
+    /// Scale a `Value` that has an i64 representation, rounding half up.
+    ///
+    /// Like a float to integer conversion, this saturates if the result overflows
+    /// and gives zero for a NaN factor.
+    /// See `scale_rounded` to choose the rounding and detect overflow.
     pub fn scale(self, factor: f64) -> Self {
-        Self(((self.0 as f64) * factor) as i64)
+        let repr = self.0;
+        self.scale_rounded(factor, Rounding::HalfUp)
+            .unwrap_or(Self((repr as f64 * factor) as i64))
+    }
+
+    /// Scale a `Value` that has an i64 representation with the given rounding.
+    ///
+    /// The factor is taken to be the decimal number it displays as,
+    /// so that `0.2` is exactly one fifth, and the product is computed exactly before rounding.
+    pub fn scale_rounded(self, factor: f64, rounding: Rounding) -> Result<Self, Error> {
+        let (numerator, denominator) = scale_factor(factor)?;
+        Ok(Self(scale(self.0, numerator, denominator, rounding)?))
+    }
+
+    /// Scale a `Value` that has an i64 representation by an exact ratio with the given rounding.
+    pub fn scale_ratio(
+        self,
+        numerator: i64,
+        denominator: i64,
+        rounding: Rounding,
+    ) -> Result<Self, Error> {
+        Ok(Self(scale(
+            self.0,
+            numerator.into(),
+            denominator.into(),
+            rounding,
+        )?))
+    }
+
+    /// Round a `Value` that has an i64 representation to a coarser increment.
+    /// Only `Rounding::Cash` has an effect since the representation is already integral.
+    /// A result beyond the range of i64 saturates.
+    pub fn round(self, rounding: Rounding) -> Self {
+        let saturated = if self.0 < 0 { i64::MIN } else { i64::MAX };
+        Self(
+            rounding
+                .divide(self.0.into(), 1)
+                .try_into()
+                .unwrap_or(saturated),
+        )
+    }
+
+    /// Multiply a `Value` that has an i64 representation, detecting overflow.
+    pub fn checked_mul(self, factor: i64) -> Result<Self, Error> {
+        Ok(Self(
+            self.0
+                .checked_mul(factor)
+                .ok_or("arithmetic overflow in multiplied value")?,
+        ))
+    }
+
+    /// Divide a `Value` that has an i64 representation, truncating toward zero.
+    /// Division by zero is an error rather than a panic.
+    /// See `scale_ratio` to choose the rounding.
+    pub fn checked_div(self, divisor: i64) -> Result<Self, Error> {
+        Ok(Self(
+            self.0
+                .checked_div(divisor)
+                .ok_or("division by zero or overflow in divided value")?,
+        ))
+    }
+}
+
+/// The arithmetic of a numeric `Quantity::Repr`, which gives `Value` its checked operations,
+/// `abs`, `Sum` and ratios.  Implemented for `i64` and `f64`.
+/// For `f64`, a checked operation fails if its result is not finite.
+pub trait Numeric: Copy + PartialOrd {
+    const ZERO: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn to_f64(self) -> f64;
+}
+
+impl Numeric for i64 {
+    const ZERO: Self = 0;
+
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        i64::checked_add(self, rhs)
+    }
+
+    fn checked_sub(self, rhs: Self) -> Option<Self> {
+        i64::checked_sub(self, rhs)
+    }
+
+    fn checked_neg(self) -> Option<Self> {
+        i64::checked_neg(self)
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Numeric for f64 {
+    const ZERO: Self = 0.0;
+
+    fn checked_add(self, rhs: Self) -> Option<Self> {
+        Some(self + rhs).filter(|x| x.is_finite())
+    }
+
+    fn checked_sub(self, rhs: Self) -> Option<Self> {
+        Some(self - rhs).filter(|x| x.is_finite())
+    }
+
+    fn checked_neg(self) -> Option<Self> {
+        Some(-self).filter(|x| x.is_finite())
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl<Q> Value<Q>
+where
+    Q: Quantity,
+    Q::Repr: Numeric,
+{
+    /// Add values, detecting overflow.
+    pub fn checked_add(self, rhs: Self) -> Result<Self, Error> {
+        Ok(Self(
+            self.0
+                .checked_add(rhs.0)
+                .ok_or("arithmetic overflow in added value")?,
+        ))
+    }
+
+    /// Subtract values, detecting overflow.
+    pub fn checked_sub(self, rhs: Self) -> Result<Self, Error> {
+        Ok(Self(
+            self.0
+                .checked_sub(rhs.0)
+                .ok_or("arithmetic overflow in subtracted value")?,
+        ))
+    }
+
+    /// Negate a value, detecting overflow.
+    pub fn checked_neg(self) -> Result<Self, Error> {
+        Ok(Self(
+            self.0
+                .checked_neg()
+                .ok_or("arithmetic overflow in negated value")?,
+        ))
+    }
+
+    /// The magnitude of a value, detecting overflow.
+    pub fn checked_abs(self) -> Result<Self, Error> {
+        if self.0 < Q::Repr::ZERO {
+            self.checked_neg()
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// The magnitude of a value.  Like `i64::abs`, this panics if the result overflows.
+    pub fn abs(self) -> Self {
+        self.checked_abs().expect("overflow in Value::abs")
+    }
+
+    /// Sum values, detecting overflow.
+    pub fn checked_sum(values: impl IntoIterator<Item = Self>) -> Result<Self, Error> {
+        values
+            .into_iter()
+            .try_fold(Self(Q::Repr::ZERO), |total, value| total.checked_add(value))
+    }
+
+    /// The ratio of two values, which is an error if the divisor is zero.
+    pub fn checked_ratio(self, rhs: Self) -> Result<Value<Rate>, Error> {
+        if rhs.0 == Q::Repr::ZERO {
+            Err("division by zero value")?
+        }
+        Ok(self / rhs)
+    }
+}
+
+impl<Q> Value<Q>
+where
+    Q: Quantity,
+    Q::Repr: PartialOrd,
+{
+    /// The lesser of two values, or `self` if they are equal or unordered.
+    pub fn min(self, other: Self) -> Self {
+        if other < self {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// The greater of two values, or `self` if they are equal or unordered.
+    pub fn max(self, other: Self) -> Self {
+        if other > self {
+            other
+        } else {
+            self
+        }
     }
 }
 
@@ -148,16 +366,62 @@ where
     }
 }
 
-// A Quantity can be scaled if the representation can be scaled
-impl<Q, S> Mul<S> for Value<Q>
+// A Quantity can be scaled by a primitive number if the representation can be scaled.
+// The scalar types are listed so that other `Mul` impls between `Value`s do not overlap.
+macro_rules! scalar_mul {
+    ($($s:ty),*) => {
+        $(
+            impl<Q> Mul<$s> for Value<Q>
+            where
+                Q: Quantity,
+                Q::Repr: Mul<$s, Output = Q::Repr>,
+            {
+                type Output = Self;
+
+                fn mul(self, rhs: $s) -> Self::Output {
+                    Self(self.0 * rhs)
+                }
+            }
+        )*
+    };
+}
+
+scalar_mul!(i64, i32, u32, f64);
+
+// A Quantity can be divided by a primitive number if the representation can be divided.
+// Like integer division, an i64 representation is truncated and division by zero panics.
+// See `checked_div` and `scale_ratio`.
+macro_rules! scalar_div {
+    ($($s:ty),*) => {
+        $(
+            impl<Q> Div<$s> for Value<Q>
+            where
+                Q: Quantity,
+                Q::Repr: Div<$s, Output = Q::Repr>,
+            {
+                type Output = Self;
+
+                fn div(self, rhs: $s) -> Self::Output {
+                    Self(self.0 / rhs)
+                }
+            }
+        )*
+    };
+}
+
+scalar_div!(i64, i32, u32, f64);
+
+// The ratio of two values of the same species is a dimensionless `Rate`.
+// A zero divisor gives an infinite or NaN rate.  See `checked_ratio`.
+impl<Q> Div<Self> for Value<Q>
 where
     Q: Quantity,
-    Q::Repr: Mul<S, Output = Q::Repr>,
+    Q::Repr: Numeric,
 {
-    type Output = Self;
+    type Output = Value<Rate>;
 
-    fn mul(self, rhs: S) -> Self::Output {
-        Self(self.0 * rhs)
+    fn div(self, rhs: Self) -> Self::Output {
+        Value(self.0.to_f64() / rhs.0.to_f64())
     }
 }
 
@@ -174,6 +438,40 @@ where
     }
 }
 
+// A Quantity can be subtracted from the same species if representations can be subtracted
+impl<Q> Sub<Self> for Value<Q>
+where
+    Q: Quantity,
+    Q::Repr: Sub<Q::Repr, Output = Q::Repr>,
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0 - rhs.0)
+    }
+}
+
+// Values of a Quantity can be summed if representations are numeric
+impl<Q> Sum for Value<Q>
+where
+    Q: Quantity,
+    Q::Repr: Numeric + Add<Q::Repr, Output = Q::Repr>,
+{
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self(Q::Repr::ZERO), |total, value| total + value)
+    }
+}
+
+impl<'a, Q> Sum<&'a Value<Q>> for Value<Q>
+where
+    Q: Quantity,
+    Q::Repr: Numeric + Add<Q::Repr, Output = Q::Repr>,
+{
+    fn sum<I: Iterator<Item = &'a Value<Q>>>(iter: I) -> Self {
+        iter.fold(Self(Q::Repr::ZERO), |total, value| total + Self(value.0))
+    }
+}
+
 // A Quantity can be negated if its representation can be negated
 impl<Q> Neg for Value<Q>
 where
@@ -231,3 +529,47 @@ where
         f.write_str(&Q::format(&self.0))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::quantity::{money::AUD, proportion::Percent, unit::Kilometre};
+
+    #[test]
+    fn value_algebra() {
+        type C = Value<AUD>;
+        let fee = C::from_repr(31035);
+        let paid = C::from_repr(20000);
+        assert_eq!(fee.clone() - paid.clone(), C::from_repr(11035));
+        assert_eq!((paid.clone() - fee.clone()).abs(), C::from_repr(11035));
+        assert_eq!(fee.clone() / 3, C::from_repr(10345));
+        assert_eq!((paid.clone() / fee.clone()).to_repr(), 20000.0 / 31035.0);
+        assert_eq!(
+            [fee.clone(), paid.clone()].iter().sum::<C>(),
+            C::from_repr(51035)
+        );
+        assert_eq!(Vec::<C>::new().into_iter().sum::<C>(), C::from_repr(0));
+        assert_eq!(fee.clone().min(paid.clone()), paid);
+        assert_eq!(
+            Value::<Percent>::from_repr(0.1)
+                .max(Value::from_repr(0.2))
+                .to_repr(),
+            0.2
+        );
+        assert_eq!(
+            Value::<Kilometre>::from_repr(10.0) / 4.0,
+            Value::from_repr(2.5)
+        );
+
+        let big = C::from_repr(i64::MAX);
+        assert!(big.clone().checked_add(fee.clone()).is_err());
+        assert!(C::from_repr(i64::MIN).checked_abs().is_err());
+        assert!(C::checked_sum([big, fee.clone()]).is_err());
+        assert!(fee.clone().checked_div(0).is_err());
+        assert!(fee.clone().checked_ratio(C::from_repr(0)).is_err());
+        assert_eq!(fee.checked_sub(paid).unwrap(), C::from_repr(11035));
+        assert!(Value::<Percent>::from_repr(f64::MAX)
+            .checked_add(Value::from_repr(f64::MAX))
+            .is_err());
+    }
+}
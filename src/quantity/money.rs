@@ -1,98 +1,162 @@
-use crate::quantity::Quantity;
+use super::{
+    currency::{Currency, CurrencyInfo},
+    rounding::Rounding,
+    Value,
+};
 use crate::variant::Error;
 use nom::{
-    bytes::complete::tag,
-    character::complete::{digit1, multispace0, one_of},
-    combinator::{all_consuming, opt},
-    sequence::{preceded, terminated, tuple},
+    branch::alt,
+    bytes::complete::{tag, tag_no_case},
+    character::complete::{char, digit0, digit1, multispace0, one_of},
+    combinator::{all_consuming, opt, recognize},
+    multi::many0,
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
 };
 
-/// Australian currency.
-pub struct AUD;
-
-impl Quantity for AUD {
-    type Repr = i64;
-
-    fn parse(text: &str) -> Result<Self::Repr, Error> {
-        money_from_str::<2>(text, "$")
-    }
-
-    fn format(value: &Self::Repr) -> String {
-        money_to_str::<2>(*value, "$")
-    }
+// The common currencies are re-exported here. See `currency` for the rest.
+pub use super::currency::{AUD, USD};
+
+/// Parse a money amount, rounding any fraction digits beyond the currency precision.
+///
+/// `Quantity::parse` for a currency rejects such amounts, which is equivalent to
+/// a `rounding` of `None`.  Otherwise the given `Rounding` is applied.
+///
+/// The accepted forms are as for `Quantity::parse`, for example:
+///
+/// - `$1,234.56` and `1234.56` with optional thousands separators.
+/// - `AUD 12.34` and `12.34 AUD` with the currency code before or after the amount.
+/// - `-$12.34`, `$-12.34` and `12.34-` with a leading or trailing minus.
+/// - `($12.34)` for an accounting-style negative.
+pub fn parse_money<C: Currency>(text: &str, rounding: Option<Rounding>) -> Result<Value<C>, Error> {
+    Ok(Value(money_from_str(text, &C::INFO, rounding)?))
 }
 
-/// US currency.
-pub struct USD;
-
-impl Quantity for USD {
-    type Repr = i64;
-
-    fn parse(text: &str) -> Result<Self::Repr, Error> {
-        money_from_str::<2>(text, "$")
-    }
-
-    fn format(value: &Self::Repr) -> String {
-        money_to_str::<2>(*value, "$")
-    }
+type Parsed<'a> = (
+    Option<char>,
+    Option<&'a str>,
+    Option<char>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<char>,
+);
+
+fn amount<'a>(currency: &CurrencyInfo, input: &'a str) -> IResult<&'a str, Parsed<'a>> {
+    let spaces = multispace0;
+    let symbol = |i| alt((tag_no_case(currency.code), tag(currency.symbol)))(i);
+    let whole = recognize(pair(digit1, many0(preceded(char(','), digit1))));
+    tuple((
+        opt(terminated(one_of("+-"), spaces)),
+        opt(terminated(symbol, spaces)),
+        opt(terminated(one_of("+-"), spaces)),
+        opt(whole),
+        opt(preceded(char('.'), digit0)),
+        preceded(spaces, opt(terminated(symbol, spaces))),
+        opt(terminated(char('-'), spaces)),
+    ))(input)
 }
 
-fn money_from_str<const PRECISION: u32>(input: &str, symbol: &str) -> Result<i64, Error> {
+pub(crate) fn money_from_str(
+    input: &str,
+    currency: &CurrencyInfo,
+    rounding: Option<Rounding>,
+) -> Result<i64, Error> {
     let error = "error in money value";
     let spaces = multispace0::<&str, nom::error::Error<&str>>;
-    let digits = digit1::<&str, nom::error::Error<&str>>;
-
-    let (_, (sign1, _, sign2, whole, frac)) = all_consuming(terminated(
-        tuple((
-            opt(terminated(one_of("+-"), spaces)),
-            opt(terminated(tag(symbol), spaces)),
-            opt(terminated(one_of("+-"), spaces)),
-            opt(digits),
-            opt(preceded(tag("."), opt(digits))),
-        )),
-        spaces,
-    ))(input)
-    .or(Err(error))?;
+    let precision = currency.precision;
+
+    let parenthesised = delimited(
+        terminated(char('('), spaces),
+        |i| amount(currency, i),
+        terminated(char(')'), spaces),
+    );
+    let (_, (negative, (sign1, symbol1, sign2, whole, frac, symbol2, sign3))) =
+        all_consuming(preceded(
+            spaces,
+            alt((
+                pair(|i| Ok((i, true)), parenthesised),
+                pair(|i| Ok((i, false)), |i| amount(currency, i)),
+            )),
+        ))(input)
+        .or(Err(error))?;
+
+    if symbol1.is_some() && symbol2.is_some() {
+        Err(error)?
+    }
 
-    let mut value: i64 = 0;
+    let signs = [sign1, sign2, sign3];
+    if signs.iter().flatten().count() + negative as usize > 1 {
+        Err(error)?
+    }
+    let negative = negative || signs.contains(&Some('-'));
 
-    if let Some(x) = whole {
-        value += x.parse::<i64>().or(Err(error))? * 10i64.pow(PRECISION);
+    let whole = whole.unwrap_or("");
+    let groups: Vec<&str> = whole.split(',').collect();
+    if groups.len() > 1 && (groups[0].len() > 3 || groups[1..].iter().any(|g| g.len() != 3)) {
+        Err("misplaced thousands separator in money value")?
+    }
+    let frac = frac.unwrap_or("");
+    if whole.is_empty() && frac.is_empty() {
+        Err(error)?
     }
 
-    if let Some(Some(x)) = frac {
-        let mut frac = x.parse::<i64>().or(Err(error))?;
-        let prec = x.len() as u32;
-        if prec < PRECISION {
-            frac *= 10i64.pow(PRECISION - prec);
-        } else if prec > PRECISION {
-            frac /= 10i64.pow(prec - PRECISION);
-        }
-        value += frac;
+    let digits: String = groups.concat() + frac;
+    let mut value: i128 = if digits.is_empty() {
+        0
+    } else {
+        digits.parse().or(Err("money value out of range"))?
+    };
+
+    let range = "money value out of range";
+    let places = frac.len() as u32;
+    if places < precision {
+        value = 10i128
+            .checked_pow(precision - places)
+            .and_then(|scale| value.checked_mul(scale))
+            .ok_or(range)?;
+    } else if places > precision {
+        let rounding = rounding.ok_or("too many decimal places in money value")?;
+        let scale = 10i128.checked_pow(places - precision).ok_or(range)?;
+        value = rounding.divide(value, scale);
     }
 
-    match (sign1, sign2) {
-        (Some('-'), None) | (None, Some('-')) => {
-            value = -value;
-        }
-        (None, None) => {}
-        _ => Err(error)?,
+    if negative {
+        value = -value;
     }
 
-    Ok(value)
+    Ok(value.try_into().or(Err("money value out of range"))?)
+}
+
+/// Format an amount with the currency symbol e.g. `$12.34`.
+/// An alphabetic symbol is separated from the amount by a space.
+pub(crate) fn money_to_str(value: i64, currency: &CurrencyInfo) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+    let symbol = currency.symbol;
+    let space = if symbol.ends_with(char::is_alphabetic) {
+        " "
+    } else {
+        ""
+    };
+    let amount = amount_to_str(value.unsigned_abs(), currency.precision);
+    format!("{sign}{symbol}{space}{amount}")
 }
 
-fn money_to_str<const PRECISION: u32>(value: i64, symbol: &str) -> String {
+/// Format an amount with the currency code e.g. `AUD 12.34`.
+pub(crate) fn money_to_code_str(value: i64, currency: &CurrencyInfo) -> String {
     let sign = if value < 0 { "-" } else { "" };
-    let magnitude = format!("{:01$}", value.abs(), (PRECISION + 1) as usize);
-    let split = magnitude.len() - PRECISION as usize;
-    format!(
-        "{}{}{}.{}",
-        sign,
-        symbol,
-        &magnitude[..split],
-        &magnitude[split..]
-    )
+    let amount = amount_to_str(value.unsigned_abs(), currency.precision);
+    format!("{sign}{} {amount}", currency.code)
+}
+
+fn amount_to_str(magnitude: u64, precision: u32) -> String {
+    let magnitude = format!("{:01$}", magnitude, (precision + 1) as usize);
+    let split = magnitude.len() - precision as usize;
+    if precision == 0 {
+        magnitude
+    } else {
+        format!("{}.{}", &magnitude[..split], &magnitude[split..])
+    }
 }
 
 #[cfg(test)]
@@ -132,6 +196,55 @@ mod test {
         assert_eq!(C::from_repr(3410), "34.1".parse::<C>().unwrap());
     }
 
+    #[test]
+    fn currency_value_parsing_extended() {
+        type C = Value<AUD>;
+        assert_eq!(C::from_repr(123456), "$1,234.56".parse::<C>().unwrap());
+        assert_eq!(C::from_repr(123456700), "1,234,567".parse::<C>().unwrap());
+        assert_eq!(C::from_repr(-1234), "($12.34)".parse::<C>().unwrap());
+        assert_eq!(C::from_repr(-1234), "( AUD 12.34 )".parse::<C>().unwrap());
+        assert_eq!(C::from_repr(-1234), "12.34-".parse::<C>().unwrap());
+        assert_eq!(C::from_repr(-1234), "$12.34 -".parse::<C>().unwrap());
+        assert_eq!(C::from_repr(1234), "12.34 AUD".parse::<C>().unwrap());
+        assert_eq!(C::from_repr(1234), "aud12.34".parse::<C>().unwrap());
+        for bad in [
+            "",
+            "$",
+            "1,23.45",
+            "12,3456",
+            "(-$1)",
+            "-1-",
+            "AUD 1 AUD",
+            "1.2.3",
+            "12.345",
+        ] {
+            assert!(bad.parse::<C>().is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn currency_value_parsing_rounding() {
+        let parse = |text, rounding| parse_money::<AUD>(text, rounding).unwrap().to_repr();
+        assert_eq!(parse("12.345", Some(Rounding::HalfUp)), 1235);
+        let long = |digits| format!("0.{digits}{}", "0".repeat(37));
+        let long_parse = |digits, rounding| {
+            parse_money::<AUD>(&long(digits), Some(rounding))
+                .unwrap()
+                .to_repr()
+        };
+        assert_eq!(long_parse("009", Rounding::HalfUp), 1);
+        assert_eq!(long_parse("011", Rounding::Cash), 0);
+        let tiny = format!("0.{}1", "0".repeat(45));
+        assert!(parse_money::<AUD>(&tiny, Some(Rounding::HalfUp)).is_err());
+        assert!("99999999999999999999999999999999999999"
+            .parse::<Value<AUD>>()
+            .is_err());
+        assert_eq!(parse("12.345", Some(Rounding::HalfEven)), 1234);
+        assert_eq!(parse("12.349", Some(Rounding::TowardZero)), 1234);
+        assert_eq!(parse("(12.345)", Some(Rounding::HalfUp)), -1235);
+        assert!(parse_money::<AUD>("12.345", None).is_err());
+    }
+
     #[test]
     fn currency_value_comparisons() {
         type C = Value<AUD>;
@@ -0,0 +1,472 @@
+use super::{
+    date::{Date, DateLocale, LocalDate},
+    Quantity, Value,
+};
+use crate::{
+    table::{Ident, Table},
+    variant::{Error, Variant},
+};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use std::{
+    fmt::Write,
+    ops::{Add, Neg, Sub},
+    rc::Rc,
+};
+
+/// A calendar duration quantity such as `3 months` or `1 year 10 days`.
+///
+/// Years and weeks are parsed and reduced to months and days.
+/// A duration is added to a date month first, and the day of month is limited to the end of the month:
+///
+/// ```
+/// use ruly::quantity::{date::Date, period::Duration, Value};
+/// let start: Value<Date> = "31/01/2024".parse().unwrap();
+/// let term: Value<Duration> = "1 month 1 day".parse().unwrap();
+/// assert_eq!((start + term).to_string(), "01/03/2024");
+/// ```
+pub struct Duration;
+
+/// The representation of a `Duration`.
+///
+/// This converts to and from a `Variant::Table` with entries `months` and `days`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub months: i64,
+    pub days: i64,
+}
+
+/// An inclusive range of dates quantity such as `2024-07-01..2025-06-30`.
+pub struct DateRange;
+
+/// The representation of a `DateRange`.
+///
+/// This converts to and from a `Variant::Table` with entries `start` and `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Interval {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Span {
+    pub fn days(days: i64) -> Self {
+        Self { months: 0, days }
+    }
+
+    pub fn months(months: i64) -> Self {
+        Self { months, days: 0 }
+    }
+}
+
+// Months and days saturate rather than overflow, as no date is that far away.
+impl Add for Span {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            months: self.months.saturating_add(rhs.months),
+            days: self.days.saturating_add(rhs.days),
+        }
+    }
+}
+
+impl Neg for Span {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            months: self.months.saturating_neg(),
+            days: self.days.saturating_neg(),
+        }
+    }
+}
+
+impl Interval {
+    /// Construct an interval, which must not end before it starts.
+    pub fn new(start: NaiveDate, end: NaiveDate) -> Result<Self, Error> {
+        if end < start {
+            Err("date range ends before it starts")?
+        }
+        Ok(Self { start, end })
+    }
+
+    pub fn contains(&self, date: &NaiveDate) -> bool {
+        self.start <= *date && *date <= self.end
+    }
+
+    /// The number of days in the interval, counting both ends.
+    pub fn len_days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+}
+
+impl Quantity for Duration {
+    type Repr = Span;
+
+    /// A duration is a sequence of parts such as `1 year, 2 weeks 1d` with an optional
+    /// leading `-` that negates the whole.  A part may also be negated relative to the whole
+    /// e.g. `1 month -3 days`.
+    fn parse(text: &str) -> Result<Self::Repr, Error> {
+        let error = || Error::Detail(format!("error in duration value '{text}'"));
+        let (sign, rest) = match text.trim().strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, text.trim()),
+        };
+        let mut span = Span::default();
+        let mut rest = rest.trim_start();
+        if rest.is_empty() {
+            Err(error())?
+        }
+        let mut first = true;
+        while !rest.is_empty() {
+            let (part_sign, part) = match rest.strip_prefix('-') {
+                Some(part) if !first => (-1, part),
+                _ => (1, rest),
+            };
+            first = false;
+            rest = part;
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let number: i64 = rest[..digits].parse().or(Err(error()))?;
+            let number = number * part_sign;
+            rest = rest[digits..].trim_start();
+            let word = rest
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(rest.len());
+            let (total, scale) = match &rest[..word] {
+                "y" | "year" | "years" => (&mut span.months, 12),
+                "m" | "mo" | "month" | "months" => (&mut span.months, 1),
+                "w" | "week" | "weeks" => (&mut span.days, 7),
+                "d" | "day" | "days" => (&mut span.days, 1),
+                _ => Err(error())?,
+            };
+            *total = number
+                .checked_mul(scale)
+                .and_then(|n| total.checked_add(n))
+                .ok_or_else(error)?;
+            rest = rest[word..].trim_start_matches([' ', ',']);
+        }
+        Ok(Span {
+            months: span.months.checked_mul(sign).ok_or_else(error)?,
+            days: span.days.checked_mul(sign).ok_or_else(error)?,
+        })
+    }
+
+    /// The sign of the months, or of the days if there are none, is written once in front.
+    fn format(value: &Self::Repr) -> String {
+        let negative = value.months < 0 || value.months == 0 && value.days < 0;
+        let sign = if negative { -1 } else { 1 };
+        let (months, days) = (
+            value.months.saturating_mul(sign),
+            value.days.saturating_mul(sign),
+        );
+        let mut text = String::new();
+        let mut part = |n: i64, unit: &str| {
+            if !text.is_empty() {
+                text.push(' ');
+            }
+            let plural = if n.abs() == 1 { "" } else { "s" };
+            let _ = write!(text, "{n} {unit}{plural}");
+        };
+        let (years, months) = (months / 12, months % 12);
+        if years != 0 {
+            part(years, "year");
+        }
+        if months != 0 {
+            part(months, "month");
+        }
+        if days != 0 || value == &Span::default() {
+            part(days, "day");
+        }
+        if negative {
+            text.insert(0, '-');
+        }
+        text
+    }
+}
+
+impl Quantity for DateRange {
+    type Repr = Interval;
+
+    fn parse(text: &str) -> Result<Self::Repr, Error> {
+        let (start, end) = text.split_once("..").ok_or("expected '..' in date range")?;
+        Interval::new(Date::parse(start.trim())?, Date::parse(end.trim())?)
+    }
+
+    fn format(value: &Self::Repr) -> String {
+        format!(
+            "{}..{}",
+            Date::format(&value.start),
+            Date::format(&value.end)
+        )
+    }
+}
+
+static MONTHS: Ident = Ident::Intern("months");
+static DAYS: Ident = Ident::Intern("days");
+static START: Ident = Ident::Intern("start");
+static END: Ident = Ident::Intern("end");
+
+fn entry<A: TryFrom<Variant>>(table: &Table, name: &Ident) -> Result<A, Error> {
+    table
+        .get(name)
+        .cloned()
+        .and_then(|v| v.try_into().ok())
+        .ok_or_else(|| {
+            Error::Detail(format!(
+                "expected {} in table",
+                name.as_str().unwrap_or_default()
+            ))
+        })
+}
+
+impl From<Span> for Variant {
+    fn from(value: Span) -> Self {
+        let mut table = Table::new();
+        table.join_entry(MONTHS.clone(), value.months.into());
+        table.join_entry(DAYS.clone(), value.days.into());
+        Rc::new(table).into()
+    }
+}
+
+impl TryFrom<Variant> for Span {
+    type Error = Error;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        let table = value.as_table().ok_or("expected a table for a duration")?;
+        Ok(Span {
+            months: entry(table, &MONTHS)?,
+            days: entry(table, &DAYS)?,
+        })
+    }
+}
+
+impl From<Interval> for Variant {
+    fn from(value: Interval) -> Self {
+        let mut table = Table::new();
+        table.join_entry(START.clone(), value.start.into());
+        table.join_entry(END.clone(), value.end.into());
+        Rc::new(table).into()
+    }
+}
+
+impl TryFrom<Variant> for Interval {
+    type Error = Error;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        let table = value
+            .as_table()
+            .ok_or("expected a table for a date range")?;
+        Interval::new(entry(table, &START)?, entry(table, &END)?)
+    }
+}
+
+fn add_span(date: NaiveDate, span: Span) -> Option<NaiveDate> {
+    let months = Months::new(span.months.unsigned_abs().try_into().ok()?);
+    let date = if span.months < 0 {
+        date.checked_sub_months(months)
+    } else {
+        date.checked_add_months(months)
+    }?;
+    let days = Days::new(span.days.unsigned_abs());
+    if span.days < 0 {
+        date.checked_sub_days(days)
+    } else {
+        date.checked_add_days(days)
+    }
+}
+
+// A date plus a duration is a date.
+// Panics if the result is out of range.  See `checked_add_duration`.
+impl<L: DateLocale> Add<Value<Duration>> for Value<LocalDate<L>> {
+    type Output = Self;
+
+    fn add(self, rhs: Value<Duration>) -> Self::Output {
+        self.checked_add_duration(&rhs).expect("date out of range")
+    }
+}
+
+// A date minus a duration is a date.
+// Panics if the result is out of range.  See `checked_add_duration`.
+impl<L: DateLocale> Sub<Value<Duration>> for Value<LocalDate<L>> {
+    type Output = Self;
+
+    fn sub(self, rhs: Value<Duration>) -> Self::Output {
+        self.checked_add_duration(&-rhs).expect("date out of range")
+    }
+}
+
+impl<L: DateLocale> Value<LocalDate<L>> {
+    /// This date plus a duration, or an error if the result is out of range.
+    pub fn checked_add_duration(&self, duration: &Value<Duration>) -> Result<Self, Error> {
+        add_span(self.0, duration.0)
+            .map(Value)
+            .ok_or_else(|| Error::Detail(format!("date out of range adding {duration} to {self}")))
+    }
+
+    /// The number of days from an earlier date to this one.
+    pub fn days_since(&self, earlier: &Self) -> i64 {
+        (self.0 - earlier.0).num_days()
+    }
+
+    /// The duration in whole months and days from this date to a later one,
+    /// such that adding it to this date gives the later date.
+    pub fn duration_until(&self, later: &Self) -> Value<Duration> {
+        if later.0 < self.0 {
+            return -later.duration_until(self);
+        }
+        let (a, b) = (self.0, later.0);
+        let mut months = (b.year() - a.year()) as i64 * 12 + b.month() as i64 - a.month() as i64;
+        if b.day() < a.day() {
+            months -= 1;
+        }
+        let anchor = add_span(a, Span::months(months)).expect("an earlier month is in range");
+        Value(Span {
+            months,
+            days: (b - anchor).num_days(),
+        })
+    }
+
+    /// The age in completed years on the given date of a person born on this date.
+    ///
+    /// A person born on 29 February attains each age on 1 March in a common year.
+    pub fn age_on(&self, on: &Self) -> i64 {
+        let (birth, on) = (self.0, on.0);
+        let years = (on.year() - birth.year()) as i64;
+        if (on.month(), on.day()) < (birth.month(), birth.day()) {
+            years - 1
+        } else {
+            years
+        }
+    }
+
+    /// True iff this date is within the given range.
+    pub fn within(&self, range: &Value<DateRange>) -> bool {
+        range.0.contains(&self.0)
+    }
+}
+
+impl Value<DateRange> {
+    /// True iff the two ranges have a date in common.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.0.start <= other.0.end && other.0.start <= self.0.end
+    }
+
+    /// The duration from the start of the range to the day after its end.
+    pub fn duration(&self) -> Value<Duration> {
+        let start = Value::<Date>(self.0.start);
+        start.duration_until(&Value(self.0.end + Days::new(1)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn date(text: &str) -> Value<Date> {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn duration_and_range_values() {
+        type D = Value<Duration>;
+        assert_eq!("3 months".parse::<D>().unwrap().to_repr(), Span::months(3));
+        assert_eq!(
+            "1 year, 2 weeks 1d".parse::<D>().unwrap().to_repr(),
+            Span {
+                months: 12,
+                days: 15
+            }
+        );
+        assert_eq!("-10 days".parse::<D>().unwrap().to_repr(), Span::days(-10));
+        assert!("3 fortnights".parse::<D>().is_err());
+        assert!("".parse::<D>().is_err());
+        assert_eq!(
+            D::from_repr(Span {
+                months: 15,
+                days: 1
+            })
+            .to_string(),
+            "1 year 3 months 1 day"
+        );
+        assert_eq!(D::from_repr(Span::default()).to_string(), "0 days");
+        assert!("999999999999999999 years".parse::<D>().is_err());
+        for span in [
+            Span {
+                months: -15,
+                days: -3,
+            },
+            Span {
+                months: -1,
+                days: 3,
+            },
+            Span::days(-3),
+        ] {
+            let text = D::from_repr(span).to_string();
+            assert_eq!(text.parse::<D>().unwrap().to_repr(), span, "{text}");
+        }
+        assert_eq!(
+            D::from_repr(Span {
+                months: -15,
+                days: -3
+            })
+            .to_string(),
+            "-1 year 3 months 3 days"
+        );
+
+        let range: Value<DateRange> = "2024-07-01..2025-06-30".parse().unwrap();
+        assert_eq!(range.to_string(), "01/07/2024..30/06/2025");
+        assert_eq!(range.duration().to_string(), "1 year");
+        assert!(date("30/06/2025").within(&range));
+        assert!(!date("01/07/2025").within(&range));
+        assert!("2025-07-01..2024-06-30"
+            .parse::<Value<DateRange>>()
+            .is_err());
+
+        let variant: Variant = range.clone().into();
+        assert_eq!(Value::<DateRange>::try_from(variant).unwrap(), range);
+        let variant: Variant = D::from_repr(Span::months(3)).into();
+        assert_eq!(D::try_from(variant).unwrap().to_repr(), Span::months(3));
+    }
+
+    #[test]
+    fn date_arithmetic_and_age() {
+        let three_months: Value<Duration> = "3 months".parse().unwrap();
+        assert_eq!(
+            date("30/11/2023") + three_months.clone(),
+            date("29/02/2024")
+        );
+        assert_eq!(date("31/05/2024") - three_months, date("29/02/2024"));
+        let far: Value<Duration> = "9999999 years".parse().unwrap();
+        assert!(date("01/01/2024").checked_add_duration(&far).is_err());
+        let extreme = Span {
+            months: i64::MIN,
+            days: i64::MAX,
+        };
+        assert_eq!(
+            -extreme + Span::days(1),
+            Span {
+                months: i64::MAX,
+                days: i64::MIN + 2
+            }
+        );
+        assert_eq!((extreme + extreme).months, i64::MIN);
+        assert_eq!(date("10/03/2024").days_since(&date("10/02/2024")), 29);
+
+        let span = date("15/01/2024").duration_until(&date("10/03/2024"));
+        assert_eq!(
+            span.clone().to_repr(),
+            Span {
+                months: 1,
+                days: 24
+            }
+        );
+        assert_eq!(date("15/01/2024") + span, date("10/03/2024"));
+
+        let birth = date("29/02/2000");
+        assert_eq!(birth.age_on(&date("28/02/2001")), 0);
+        assert_eq!(birth.age_on(&date("01/03/2001")), 1);
+        assert_eq!(birth.age_on(&date("29/02/2004")), 4);
+        assert_eq!(date("15/06/1980").age_on(&date("14/06/2024")), 43);
+    }
+}
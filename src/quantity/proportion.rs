@@ -0,0 +1,219 @@
+use super::{currency::Currency, rounding::decimal_ratio, Quantity, Value};
+use crate::variant::Error;
+use std::ops::Mul;
+
+/// A proportion formatted as a percentage e.g. `20%`.
+///
+/// The representation is the fraction, so `20%` is represented by `0.2`.
+/// Both `20%` and `0.2` are parsed.
+///
+/// A currency value can be multiplied by a proportion, giving a currency value rounded half up:
+///
+/// ```
+/// use ruly::quantity::{money::AUD, proportion::Percent, Value};
+/// let fee: Value<AUD> = "$123.45".parse().unwrap();
+/// let share: Value<Percent> = "20%".parse().unwrap();
+/// assert_eq!((fee * share).to_string(), "$24.69");
+/// ```
+///
+/// But two currency values cannot be multiplied:
+///
+/// ```compile_fail
+/// use ruly::quantity::{money::AUD, Value};
+/// let fee: Value<AUD> = "$123.45".parse().unwrap();
+/// let nonsense = fee.clone() * fee;
+/// ```
+pub struct Percent;
+
+/// A proportion formatted as a decimal fraction e.g. `0.2`.
+///
+/// Both `20%` and `0.2` are parsed.  Otherwise `Rate` is like `Percent`.
+pub struct Rate;
+
+impl Quantity for Percent {
+    type Repr = f64;
+
+    fn parse(text: &str) -> Result<Self::Repr, Error> {
+        proportion_from_str(text)
+    }
+
+    fn format(value: &Self::Repr) -> String {
+        format!("{}%", shift_decimal(&value.to_string(), 2))
+    }
+}
+
+impl Quantity for Rate {
+    type Repr = f64;
+
+    fn parse(text: &str) -> Result<Self::Repr, Error> {
+        proportion_from_str(text)
+    }
+
+    fn format(value: &Self::Repr) -> String {
+        value.to_string()
+    }
+}
+
+fn proportion_from_str(text: &str) -> Result<f64, Error> {
+    let error = "error in proportion value";
+    let text = text.trim();
+    let (number, places) = match text.strip_suffix('%') {
+        Some(number) => (number.trim_end(), -2),
+        None => (text, 0),
+    };
+    if number.is_empty()
+        || !number
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-.".contains(c))
+    {
+        Err(error)?
+    }
+    number.parse::<f64>().or(Err(error))?;
+    Ok(shift_decimal(number, places).parse().or(Err(error))?)
+}
+
+// Move the decimal point of a decimal numeral `places` to the right (or left if negative).
+// Working on the numeral avoids introducing binary rounding error.
+fn shift_decimal(text: &str, places: i32) -> String {
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (whole, frac) = text.split_once('.').unwrap_or((text, ""));
+    let digits = format!("{whole}{frac}");
+    let point = whole.len() as i32 + places;
+    let (whole, frac) = if point <= 0 {
+        (String::new(), "0".repeat(-point as usize) + &digits)
+    } else if point as usize >= digits.len() {
+        (
+            digits.clone() + &"0".repeat(point as usize - digits.len()),
+            String::new(),
+        )
+    } else {
+        let (w, f) = digits.split_at(point as usize);
+        (w.to_string(), f.to_string())
+    };
+    let whole = whole.trim_start_matches('0');
+    let frac = frac.trim_end_matches('0');
+    let whole = if whole.is_empty() { "0" } else { whole };
+    if frac.is_empty() {
+        format!("{sign}{whole}")
+    } else {
+        format!("{sign}{whole}.{frac}")
+    }
+}
+
+// Multiply two proportions as decimal numbers, so that `0.75 * 0.2` is exactly `0.15`.
+fn decimal_product(a: f64, b: f64) -> f64 {
+    match (decimal_ratio(a), decimal_ratio(b)) {
+        (Ok((n1, d1)), Ok((n2, d2))) => match (n1.checked_mul(n2), d1.checked_mul(d2)) {
+            (Some(n), Some(d)) => {
+                let places = d.ilog10() as i32;
+                shift_decimal(&n.to_string(), -places)
+                    .parse()
+                    .unwrap_or(a * b)
+            }
+            _ => a * b,
+        },
+        _ => a * b,
+    }
+}
+
+macro_rules! proportion_arithmetic {
+    ($($p:ident),*) => {
+        $(
+            // A currency value times a proportion is a currency value, rounded half up.
+            // Like `Value::scale`, this saturates if the product overflows and a NaN proportion
+            // gives zero.  See `Value::scale_rounded` to detect these.
+            impl<C: Currency> Mul<Value<$p>> for Value<C> {
+                type Output = Value<C>;
+
+                fn mul(self, rhs: Value<$p>) -> Self::Output {
+                    self.scale(rhs.0)
+                }
+            }
+
+            // A proportion times a currency value is a currency value.
+            impl<C: Currency> Mul<Value<C>> for Value<$p> {
+                type Output = Value<C>;
+
+                fn mul(self, rhs: Value<C>) -> Self::Output {
+                    rhs * self
+                }
+            }
+        )*
+    };
+}
+
+proportion_arithmetic!(Percent, Rate);
+
+macro_rules! proportion_product {
+    ($($a:ident * $b:ident = $c:ident),*) => {
+        $(
+            impl Mul<Value<$b>> for Value<$a> {
+                type Output = Value<$c>;
+
+                fn mul(self, rhs: Value<$b>) -> Self::Output {
+                    Value(decimal_product(self.0, rhs.0))
+                }
+            }
+        )*
+    };
+}
+
+proportion_product!(
+    Percent * Percent = Percent,
+    Rate * Rate = Rate,
+    Percent * Rate = Rate,
+    Rate * Percent = Rate
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::quantity::money::AUD;
+
+    #[test]
+    fn proportion_parsing_and_formatting() {
+        type P = Value<Percent>;
+        type R = Value<Rate>;
+        assert_eq!("20%".parse::<P>().unwrap(), P::from_repr(0.2));
+        assert_eq!("0.2".parse::<P>().unwrap(), P::from_repr(0.2));
+        assert_eq!(" 12.5 % ".parse::<R>().unwrap(), R::from_repr(0.125));
+        assert_eq!("-0.07%".parse::<R>().unwrap(), R::from_repr(-0.0007));
+        assert_eq!(P::from_repr(0.2).to_string(), "20%");
+        assert_eq!(P::from_repr(0.125).to_string(), "12.5%");
+        assert_eq!(P::from_repr(1.5).to_string(), "150%");
+        assert_eq!(P::from_repr(0.0007).to_string(), "0.07%");
+        assert_eq!(R::from_repr(0.2).to_string(), "0.2");
+        assert!("%".parse::<P>().is_err());
+        assert!("twenty%".parse::<P>().is_err());
+        assert!("1e3".parse::<P>().is_err());
+    }
+
+    #[test]
+    fn proportion_arithmetic() {
+        type C = Value<AUD>;
+        let fee = C::from_repr(12345);
+        let share = Value::<Percent>::from_repr(0.2);
+        let units = Value::<Rate>::from_repr(0.75);
+        assert_eq!(fee.clone() * share.clone(), C::from_repr(2469));
+        assert_eq!(share.clone() * fee.clone(), C::from_repr(2469));
+        assert_eq!((units * share).to_repr(), 0.15);
+        assert_eq!(fee * Value::<Rate>::from_repr(0.15), C::from_repr(1852));
+
+        let rate = |r| Value::<Rate>::from_repr(r);
+        assert_eq!(C::from_repr(100) * rate(f64::NAN), C::from_repr(0));
+        assert_eq!(
+            C::from_repr(100) * rate(f64::INFINITY),
+            C::from_repr(i64::MAX)
+        );
+        assert_eq!(
+            C::from_repr(-100) * rate(f64::INFINITY),
+            C::from_repr(i64::MIN)
+        );
+        assert_eq!(C::from_repr(100) * rate(1e300), C::from_repr(i64::MAX));
+        let ratio = C::from_repr(100) / C::from_repr(0);
+        assert_eq!(C::from_repr(100) * ratio, C::from_repr(i64::MAX));
+    }
+}
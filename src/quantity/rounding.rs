@@ -0,0 +1,200 @@
+use crate::variant::Error;
+
+/// A policy for discarding digits from an exact result.
+///
+/// The policies are symmetric about zero: a negative value is rounded
+/// as its magnitude would be and the sign is then restored.
+///
+/// Rounding is used when parsing money with `money::parse_money` and when
+/// scaling a `Value` with `Value::scale_rounded` or `Value::scale_ratio`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Discard the excess digits.
+    TowardZero,
+    /// Round to the nearest, with halves away from zero. The usual commercial rounding.
+    HalfUp,
+    /// Round to the nearest, with halves to the even neighbour. Also called banker's rounding.
+    HalfEven,
+    /// Round to the nearest multiple of 5 minor units, with halves away from zero.
+    /// This is Australian cash rounding to 5 cents.
+    Cash,
+}
+
+impl Rounding {
+    /// Divide `numerator` by a positive `denominator` and round the quotient.
+    ///
+    /// The remainder is compared with the rest of the denominator rather than doubled,
+    /// so no intermediate result overflows.  A `Cash` result beyond the range of i128 saturates.
+    pub fn divide(self, numerator: i128, denominator: i128) -> i128 {
+        debug_assert!(denominator > 0);
+        let quotient = numerator / denominator;
+        let remainder = (numerator % denominator).abs();
+        let rest = denominator - remainder;
+        if self == Rounding::Cash {
+            let magnitude = quotient.unsigned_abs();
+            let units = magnitude % 5;
+            let up = units > 2 || units == 2 && remainder > 0 && remainder >= rest;
+            let fives = magnitude / 5 + up as u128;
+            let rounded = i128::try_from(fives * 5).unwrap_or(i128::MAX);
+            return if numerator < 0 { -rounded } else { rounded };
+        }
+        let away = quotient + numerator.signum();
+        if remainder == 0 {
+            return quotient;
+        }
+        match self {
+            Rounding::TowardZero => quotient,
+            Rounding::HalfUp if remainder >= rest => away,
+            Rounding::HalfEven if remainder > rest => away,
+            Rounding::HalfEven if remainder == rest && quotient % 2 != 0 => away,
+            _ => quotient,
+        }
+    }
+}
+
+/// Multiply `value` by `numerator / denominator` exactly and round the result.
+/// The denominator may be negative but not zero.
+pub(crate) fn scale(
+    value: i64,
+    numerator: i128,
+    denominator: i128,
+    rounding: Rounding,
+) -> Result<i64, Error> {
+    let overflow = "arithmetic overflow in scaled value";
+    if denominator == 0 {
+        Err("scaled by a ratio with zero denominator")?
+    }
+    let (numerator, denominator) = if denominator < 0 {
+        (numerator.checked_neg().ok_or(overflow)?, -denominator)
+    } else {
+        (numerator, denominator)
+    };
+    let product = (value as i128).checked_mul(numerator).ok_or(overflow)?;
+    Ok(rounding
+        .divide(product, denominator)
+        .try_into()
+        .or(Err(overflow))?)
+}
+
+/// Express a float as the exact ratio of its shortest decimal representation.
+/// So `0.2` is taken to mean `2 / 10` rather than the nearest binary fraction.
+pub(crate) fn decimal_ratio(factor: f64) -> Result<(i128, i128), Error> {
+    let error = "scale factor out of range";
+    if !factor.is_finite() {
+        Err(error)?
+    }
+    let text = factor.to_string();
+    let (whole, frac) = text.split_once('.').unwrap_or((&text, ""));
+    let numerator = format!("{whole}{frac}").parse::<i128>().or(Err(error))?;
+    let denominator = 10i128.checked_pow(frac.len() as u32).ok_or(error)?;
+    Ok((numerator, denominator))
+}
+
+/// The ratio by which to scale an i64 for a finite factor, as given by `decimal_ratio`.
+///
+/// A magnitude too small for a decimal ratio, below about `1e-21`, is taken as zero since
+/// its product with any i64 rounds to zero.  A magnitude of `1e38` or more saturates,
+/// so that its product with any non-zero i64 overflows.
+pub(crate) fn scale_factor(factor: f64) -> Result<(i128, i128), Error> {
+    if !factor.is_finite() {
+        Err("scale factor is not finite")?
+    }
+    Ok(match decimal_ratio(factor) {
+        Ok(ratio) => ratio,
+        Err(_) if factor.abs() < 1.0 => (0, 1),
+        Err(_) if factor < 0.0 => (-i128::MAX, 1),
+        Err(_) => (i128::MAX, 1),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rounding_division() {
+        use Rounding::*;
+        let cases = [
+            // numerator, denominator, toward zero, half up, half even, cash
+            (25, 10, 2, 3, 2, 5),
+            (35, 10, 3, 4, 4, 5),
+            (-25, 10, -2, -3, -2, -5),
+            (26, 10, 2, 3, 3, 5),
+            (-24, 10, -2, -2, -2, 0),
+            (20, 10, 2, 2, 2, 0),
+            (1232, 1, 1232, 1232, 1232, 1230),
+            (1233, 1, 1233, 1233, 1233, 1235),
+            (-1238, 1, -1238, -1238, -1238, -1240),
+        ];
+        let big = 10i128.pow(38);
+        let cases = cases.into_iter().chain([
+            (big / 10 * 9, big, 0, 1, 1, 0),
+            (-(big + big / 2), big, -1, -2, -2, 0),
+            (big / 10 * 13 + big / 20, big / 10, 13, 14, 14, 15),
+            (i128::MAX, big, 1, 2, 2, 0),
+        ]);
+        for (n, d, tz, hu, he, c) in cases {
+            assert_eq!(TowardZero.divide(n, d), tz);
+            assert_eq!(HalfUp.divide(n, d), hu);
+            assert_eq!(HalfEven.divide(n, d), he);
+            assert_eq!(Cash.divide(n, d), c);
+        }
+    }
+
+    #[test]
+    fn value_scaling() {
+        use crate::quantity::{money::AUD, Value};
+        type C = Value<AUD>;
+        // 20% of $123.45 is $24.69 exactly, not $24.68 by float truncation
+        assert_eq!(C::from_repr(12345).scale(0.2), C::from_repr(2469));
+        assert_eq!(C::from_repr(12345).scale(0.1), C::from_repr(1235));
+        assert_eq!(
+            C::from_repr(12345)
+                .scale_rounded(0.1, Rounding::HalfEven)
+                .unwrap(),
+            C::from_repr(1234)
+        );
+        assert_eq!(
+            C::from_repr(12345)
+                .scale_rounded(0.2, Rounding::Cash)
+                .unwrap(),
+            C::from_repr(2470)
+        );
+        assert_eq!(
+            C::from_repr(100)
+                .scale_ratio(1, 3, Rounding::HalfUp)
+                .unwrap(),
+            C::from_repr(33)
+        );
+        assert_eq!(C::from_repr(1233).round(Rounding::Cash), C::from_repr(1235));
+        assert!(C::from_repr(i64::MAX)
+            .scale_rounded(2.0, Rounding::HalfUp)
+            .is_err());
+        assert!(C::from_repr(i64::MAX).checked_mul(2).is_err());
+        assert!(C::from_repr(1)
+            .scale_rounded(f64::NAN, Rounding::HalfUp)
+            .is_err());
+        assert_eq!(C::from_repr(7).checked_mul(-3).unwrap(), C::from_repr(-21));
+
+        assert_eq!(C::from_repr(100).scale(1e-40), C::from_repr(0));
+        assert_eq!(
+            C::from_repr(1)
+                .scale_rounded(1.2345678901234567e-22, Rounding::Cash)
+                .unwrap(),
+            C::from_repr(0)
+        );
+        assert_eq!(
+            C::from_repr(i64::MAX)
+                .scale_rounded(-1e-22, Rounding::HalfUp)
+                .unwrap(),
+            C::from_repr(0)
+        );
+        assert_eq!(C::from_repr(0).scale(1e40), C::from_repr(0));
+        assert_eq!(C::from_repr(-100).scale(1e40), C::from_repr(i64::MIN));
+        assert_eq!(C::from_repr(i64::MAX).scale(2.0), C::from_repr(i64::MAX));
+        assert_eq!(
+            C::from_repr(i64::MIN).round(Rounding::Cash),
+            C::from_repr(i64::MIN)
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use super::{
+    currency::{Currency, CurrencyInfo},
+    money::{money_from_str, money_to_str},
+    rounding::{scale, scale_factor, Rounding},
+    Quantity, Value,
+};
+use crate::variant::Error;
+use std::{
+    marker::PhantomData,
+    ops::{Div, Mul},
+};
+
+/// A unit of measure is a `Quantity` with a physical dimension, such as length or time.
+///
+/// Values are represented by `f64` and are formatted with the unit symbol e.g. `12.5 km`.
+/// Values of one unit can be added but can only be combined with values of another
+/// unit after an explicit conversion with `Value::convert`, which is only
+/// available between units of the same `Dimension`:
+///
+/// ```
+/// use ruly::quantity::{unit::{Hour, Minute}, Value};
+/// let a: Value<Minute> = "90 min".parse().unwrap();
+/// let b: Value<Hour> = "1 h".parse().unwrap();
+/// assert_eq!((a.convert::<Hour>() + b).to_string(), "2.5 h");
+/// ```
+///
+/// ```compile_fail
+/// use ruly::quantity::{unit::{Hour, Kilometre}, Value};
+/// let a: Value<Kilometre> = "90 km".parse().unwrap();
+/// let b: Value<Hour> = "1 h".parse().unwrap();
+/// let nonsense = a + b;
+/// ```
+///
+/// ```compile_fail
+/// use ruly::quantity::{unit::{Hour, Kilometre}, Value};
+/// let a: Value<Kilometre> = "90 km".parse().unwrap();
+/// let nonsense = a.convert::<Hour>();
+/// ```
+///
+/// A rate of money per unit is a `Per` quantity.  Multiplying a rate by a value of its unit, or dividing
+/// an amount of money by a value of a unit, gives a value of the correct type:
+///
+/// ```
+/// use ruly::quantity::{money::AUD, unit::{Kilometre, Per}, Value};
+/// let distance: Value<Kilometre> = "40 km".parse().unwrap();
+/// let allowance: Value<Per<AUD, Kilometre>> = "$0.885/km".parse().unwrap();
+/// let payment: Value<AUD> = distance.clone() * allowance;
+/// assert_eq!(payment.to_string(), "$35.40");
+/// let rate: Value<Per<AUD, Kilometre>> = payment / distance;
+/// assert_eq!(rate.to_string(), "$0.885/km");
+/// ```
+pub trait Unit: Quantity<Repr = f64> {
+    /// The dimension of the unit such as `Length`.
+    type Dimension;
+    /// The symbol used to format values.
+    const SYMBOL: &'static str;
+    /// The size of the unit in terms of the base unit of its dimension.
+    const SCALE: f64;
+
+    /// Recognise the symbol or an alternative spelling of it.
+    fn parse_symbol(text: &str) -> bool {
+        text == Self::SYMBOL
+    }
+}
+
+/// The dimension of units of length. The base unit is the metre.
+pub struct Length;
+
+/// The dimension of units of time. The base unit is the second.
+pub struct Time;
+
+/// The dimension of units of mass. The base unit is the gram.
+pub struct Mass;
+
+impl<U: Unit> Value<U> {
+    /// Convert a value to another unit of the same dimension.
+    pub fn convert<V>(self) -> Value<V>
+    where
+        V: Unit<Dimension = U::Dimension>,
+    {
+        Value(self.0 * U::SCALE / V::SCALE)
+    }
+}
+
+/// The number of decimal places of a `Per` rate beyond those of its currency.
+pub const RATE_PLACES: u32 = 4;
+
+/// A rate of money in currency `C` per unit `U`, such as dollars per kilometre.
+///
+/// A rate is held to `RATE_PLACES` more decimal places than its currency so that
+/// a rate such as `$0.885/km` is exact.  Values are formatted as the amount, without
+/// trailing zeros beyond the currency precision, and unit symbol separated by `/`.
+pub struct Per<C, U>(PhantomData<(C, U)>);
+
+fn rate_info<C: Currency>() -> CurrencyInfo {
+    CurrencyInfo {
+        precision: C::INFO.precision + RATE_PLACES,
+        ..C::INFO
+    }
+}
+
+impl<C, U> Quantity for Per<C, U>
+where
+    C: Currency,
+    U: Unit,
+{
+    type Repr = i64;
+
+    fn parse(text: &str) -> Result<Self::Repr, Error> {
+        let (amount, unit) = text.rsplit_once('/').ok_or("expected '/' in rate value")?;
+        if !U::parse_symbol(unit.trim()) {
+            Err(Error::Detail(format!("expected a rate per {}", U::SYMBOL)))?
+        }
+        money_from_str(amount.trim(), &rate_info::<C>(), None)
+    }
+
+    fn format(value: &Self::Repr) -> String {
+        let mut amount = money_to_str(*value, &rate_info::<C>());
+        for _ in 0..RATE_PLACES {
+            if amount.ends_with('0') {
+                amount.pop();
+            }
+        }
+        let amount = amount.strip_suffix('.').unwrap_or(&amount);
+        format!("{}/{}", amount, U::SYMBOL)
+    }
+}
+
+impl<C, U> Value<Per<C, U>>
+where
+    C: Currency,
+    U: Unit,
+{
+    /// The amount for a value of the unit at this rate, rounded half up,
+    /// or an error if it overflows.
+    pub fn checked_mul_unit(self, quantity: Value<U>) -> Result<Value<C>, Error> {
+        let (numerator, denominator) = scale_factor(quantity.0)?;
+        // A denominator too large to shift is a factor so small that the amount rounds to zero.
+        match denominator.checked_mul(10i128.pow(RATE_PLACES)) {
+            Some(denominator) => Ok(Value(scale(
+                self.0,
+                numerator,
+                denominator,
+                Rounding::HalfUp,
+            )?)),
+            None => Ok(Value(0)),
+        }
+    }
+}
+
+impl<C: Currency> Value<C> {
+    /// The rate per unit of this amount over a value of the unit, rounded half up,
+    /// or an error if the value is zero or the rate overflows.
+    pub fn checked_div_unit<U: Unit>(self, quantity: Value<U>) -> Result<Value<Per<C, U>>, Error> {
+        let overflow = "arithmetic overflow in rate value";
+        let (numerator, denominator) = scale_factor(quantity.0)?;
+        if numerator == 0 {
+            Err("rate per a zero quantity")?
+        }
+        let denominator = denominator
+            .checked_mul(10i128.pow(RATE_PLACES))
+            .ok_or(overflow)?;
+        Ok(Value(scale(
+            self.0,
+            denominator,
+            numerator,
+            Rounding::HalfUp,
+        )?))
+    }
+}
+
+// A value of a unit times a rate per that unit.
+// Like a float to integer conversion, this saturates if the amount overflows.
+// See `checked_mul_unit` to detect overflow.
+impl<C, U> Mul<Value<Per<C, U>>> for Value<U>
+where
+    C: Currency,
+    U: Unit,
+{
+    type Output = Value<C>;
+
+    fn mul(self, rhs: Value<Per<C, U>>) -> Self::Output {
+        let saturated = rhs.0 as f64 * self.0 / 10f64.powi(RATE_PLACES as i32);
+        rhs.checked_mul_unit(self)
+            .unwrap_or(Value(saturated as i64))
+    }
+}
+
+// A rate per unit times a value of that unit.
+impl<C, U> Mul<Value<U>> for Value<Per<C, U>>
+where
+    C: Currency,
+    U: Unit,
+{
+    type Output = Value<C>;
+
+    fn mul(self, rhs: Value<U>) -> Self::Output {
+        rhs * self
+    }
+}
+
+fn unit_from_str<U: Unit>(text: &str) -> Result<f64, Error> {
+    let error = || Error::Detail(format!("error in {} value", U::SYMBOL));
+    let text = text.trim();
+    let split = text
+        .find(|c: char| c.is_alphabetic() || c == 'µ')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    if !unit.is_empty() && !U::parse_symbol(unit) {
+        Err(error())?
+    }
+    number.trim().parse::<f64>().or(Err(error()))
+}
+
+macro_rules! units {
+    ($($unit:ident $dimension:ident $scale:literal [$symbol:literal $(, $alias:literal)*] $doc:literal;)*) => {
+        $(
+            #[doc = $doc]
+            pub struct $unit;
+
+            impl Unit for $unit {
+                type Dimension = $dimension;
+                const SYMBOL: &'static str = $symbol;
+                const SCALE: f64 = $scale;
+
+                fn parse_symbol(text: &str) -> bool {
+                    [$symbol $(, $alias)*].contains(&text)
+                }
+            }
+
+            impl Quantity for $unit {
+                type Repr = f64;
+
+                fn parse(text: &str) -> Result<Self::Repr, Error> {
+                    unit_from_str::<Self>(text)
+                }
+
+                fn format(value: &Self::Repr) -> String {
+                    format!("{} {}", value, $symbol)
+                }
+            }
+
+            // An amount of money divided by a value of this unit is a rate per unit.
+            // Like a float to integer conversion, this saturates if the rate overflows,
+            // including division by zero.  See `checked_div_unit` to detect these.
+            impl<C: Currency> Div<Value<$unit>> for Value<C> {
+                type Output = Value<Per<C, $unit>>;
+
+                fn div(self, rhs: Value<$unit>) -> Self::Output {
+                    let saturated = self.0 as f64 * 10f64.powi(RATE_PLACES as i32) / rhs.0;
+                    self.checked_div_unit(rhs).unwrap_or(Value(saturated as i64))
+                }
+            }
+        )*
+    };
+}
+
+units! {
+    Metre Length 1.0 ["m"] "Metres";
+    Kilometre Length 1000.0 ["km"] "Kilometres";
+    Second Time 1.0 ["s", "sec"] "Seconds";
+    Minute Time 60.0 ["min", "mins"] "Minutes";
+    Hour Time 3600.0 ["h", "hr", "hrs"] "Hours";
+    Day Time 86400.0 ["d", "day", "days"] "Days";
+    Microgram Mass 0.000001 ["mcg", "µg"] "Micrograms";
+    Milligram Mass 0.001 ["mg"] "Milligrams";
+    Gram Mass 1.0 ["g"] "Grams";
+    Kilogram Mass 1000.0 ["kg"] "Kilograms";
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::quantity::money::AUD;
+
+    #[test]
+    fn unit_parsing_and_conversion() {
+        assert_eq!(
+            "12.5 km".parse::<Value<Kilometre>>().unwrap().to_repr(),
+            12.5
+        );
+        assert_eq!(
+            "12.5km".parse::<Value<Kilometre>>().unwrap().to_repr(),
+            12.5
+        );
+        assert_eq!("7".parse::<Value<Hour>>().unwrap().to_repr(), 7.0);
+        assert_eq!(
+            "250 mcg".parse::<Value<Microgram>>().unwrap().to_repr(),
+            250.0
+        );
+        assert!("12.5 min".parse::<Value<Hour>>().is_err());
+        assert!("km".parse::<Value<Kilometre>>().is_err());
+
+        let minutes = Value::<Minute>::from_repr(90.0);
+        assert_eq!(minutes.convert::<Hour>().to_string(), "1.5 h");
+        let dose = Value::<Milligram>::from_repr(250.0);
+        assert_eq!(dose.convert::<Gram>().to_repr(), 0.25);
+    }
+
+    #[test]
+    fn rate_arithmetic() {
+        type R = Value<Per<AUD, Kilometre>>;
+        let rate: R = "$0.88/km".parse().unwrap();
+        assert_eq!(rate.clone().to_repr(), 880000);
+        assert_eq!(rate.to_string(), "$0.88/km");
+        assert_eq!("$0.885/km".parse::<R>().unwrap().to_repr(), 885000);
+        assert!("$0.1234567/km".parse::<R>().is_err());
+        assert!("$0.88/h".parse::<R>().is_err());
+        assert!("$0.88".parse::<R>().is_err());
+
+        let distance = Value::<Kilometre>::from_repr(12.3);
+        assert_eq!(
+            rate.clone() * distance.clone(),
+            Value::<AUD>::from_repr(1082)
+        );
+        assert_eq!(distance * rate, Value::<AUD>::from_repr(1082));
+
+        let total = Value::<AUD>::from_repr(10000);
+        let per_hour: Value<Per<AUD, Hour>> = total / Value::<Hour>::from_repr(3.0);
+        assert_eq!(per_hour.to_string(), "$33.333333/h");
+
+        let amount = |cents| Value::<AUD>::from_repr(cents);
+        let far = || Value::<Kilometre>::from_repr(1e40);
+        let hours = |h| Value::<Hour>::from_repr(h);
+        assert_eq!((amount(100) / far()).to_repr(), 0);
+        assert!(amount(100).checked_div_unit(hours(0.0)).is_err());
+        assert!(amount(i64::MAX).checked_div_unit(hours(0.5)).is_err());
+        assert_eq!((amount(i64::MAX) / hours(0.5)).to_repr(), i64::MAX);
+        let rate: R = "$1/km".parse().unwrap();
+        assert!(rate.clone().checked_mul_unit(far()).is_err());
+        assert_eq!((rate * far()).to_repr(), i64::MAX);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{
+    table::{Ident, IdentPath, Table},
+    variant::{Error, Variant},
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+/// A `Query` designates a group of entries in nested `Table`s.
+///
+/// Where an `IdentPath` names exactly one entry, a query is a sequence of steps
+/// separated by `/`, each of which may match several entries.  A step is one of:
+///
+/// - `name` matches the entry with that name.
+/// - `#n` matches the anonymous entry `n`.
+/// - `*` matches every entry.
+///
+/// A step may be followed by one or more filters of the form `[key=value]`.
+/// A filtered step matches the _members_ of the tables it would otherwise match,
+/// keeping those members that are tables whose entry `key` equals `value`.
+/// The value is a number, a date in `YYYY-MM-DD` form or a string, which may be quoted.
+/// A value cannot contain `"`.
+///
+/// For example, with `services` a table of service tables:
+///
+/// - `claim/services/*/fee` matches the fee of every service.
+/// - `claim/services[item=51300]/fee` matches the fee of every service with item 51300.
+///
+/// A query is written and read as a string, including by serde.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    selector: Selector,
+    filters: Vec<Filter>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(Ident),
+    Wildcard,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    key: Ident,
+    value: Literal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Literal {
+    text: String,
+    quoted: bool,
+}
+
+impl Query {
+    /// Find all entries matching this query and their paths, in no particular order.
+    pub fn evaluate<'a>(&self, table: &'a Table) -> Vec<(IdentPath, &'a Variant)> {
+        let mut found = Vec::new();
+        evaluate_steps(&self.steps, table, None, &mut found);
+        found
+    }
+}
+
+fn evaluate_steps<'a>(
+    steps: &[Step],
+    table: &'a Table,
+    at: Option<&IdentPath>,
+    found: &mut Vec<(IdentPath, &'a Variant)>,
+) {
+    let Some((step, rest)) = steps.split_first() else {
+        return;
+    };
+
+    let candidates: Vec<(&Ident, &Variant)> = match &step.selector {
+        Selector::Name(name) => table.get(name).map(|v| (name, v)).into_iter().collect(),
+        Selector::Wildcard => table.iter().collect(),
+    };
+
+    let extend = |at: Option<&IdentPath>, name: &Ident| match at {
+        Some(path) => path.clone().append(name.clone()),
+        None => IdentPath::new(name.clone()),
+    };
+
+    let mut proceed = |path: IdentPath, value: &'a Variant| {
+        if rest.is_empty() {
+            found.push((path, value))
+        } else if let Some(table) = value.as_table() {
+            evaluate_steps(rest, table, Some(&path), found)
+        }
+    };
+
+    for (name, value) in candidates {
+        let path = extend(at, name);
+        if step.filters.is_empty() {
+            proceed(path, value);
+        } else if let Some(members) = value.as_table() {
+            for (member, value) in members.iter() {
+                if step.filters.iter().all(|f| f.accepts(value)) {
+                    proceed(extend(Some(&path), member), value)
+                }
+            }
+        }
+    }
+}
+
+impl Filter {
+    fn accepts(&self, member: &Variant) -> bool {
+        match member.as_table().and_then(|t| t.get(&self.key)) {
+            Some(value) => self.value.matches(value),
+            None => false,
+        }
+    }
+}
+
+impl Literal {
+    fn matches(&self, value: &Variant) -> bool {
+        let text = self.text.as_str();
+        match value {
+            Variant::String(s) => s == text,
+            _ if self.quoted => false,
+            Variant::Int(i) => text.parse::<i64>().is_ok_and(|j| *i == j),
+            Variant::Float(x) => text.parse::<f64>().is_ok_and(|y| *x == y),
+            Variant::Date(d) => NaiveDate::parse_from_str(text, "%F").is_ok_and(|e| *d == e),
+            _ => false,
+        }
+    }
+}
+
+const RESERVED: &[char] = &['/', '[', ']', '*', '=', '#', '"'];
+
+impl FromStr for Query {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = Vec::new();
+        for text in split_steps(s) {
+            steps.push(
+                parse_step(text)
+                    .map_err(|e| Error::Detail(format!("in query step '{text}': {e}")))?,
+            );
+        }
+        Ok(Query { steps })
+    }
+}
+
+// Split a query at each `/` that is outside a filter.
+fn split_steps(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '"' if depth > 0 => quoted = !quoted,
+            '[' if !quoted => depth += 1,
+            ']' if !quoted => depth -= 1,
+            '/' if depth == 0 => {
+                parts.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+fn parse_step(text: &str) -> Result<Step, Error> {
+    let (head, mut rest) = match text.find('[') {
+        Some(i) => text.split_at(i),
+        None => (text, ""),
+    };
+
+    let selector = if head == "*" {
+        Selector::Wildcard
+    } else {
+        Selector::Name(parse_ident(head)?)
+    };
+
+    let mut filters = Vec::new();
+    while !rest.is_empty() {
+        let body = rest.strip_prefix('[').ok_or("expected '['")?;
+        let (key, body) = body.split_once('=').ok_or("expected '=' in filter")?;
+        let (value, remainder) = if let Some(quoted) = body.strip_prefix('"') {
+            let (value, remainder) = quoted.split_once('"').ok_or("unterminated string")?;
+            let literal = Literal {
+                text: value.to_string(),
+                quoted: true,
+            };
+            (literal, remainder)
+        } else {
+            let end = body.find(']').ok_or("expected ']'")?;
+            let literal = Literal {
+                text: body[..end].to_string(),
+                quoted: false,
+            };
+            (literal, &body[end..])
+        };
+        rest = remainder.strip_prefix(']').ok_or("expected ']'")?;
+        if value.text.is_empty() && !value.quoted {
+            Err("empty filter value")?
+        }
+        if value.text.contains('"') && !value.quoted {
+            Err("quote in unquoted filter value")?
+        }
+        filters.push(Filter {
+            key: parse_ident(key)?,
+            value,
+        });
+    }
+
+    Ok(Step { selector, filters })
+}
+
+fn parse_ident(text: &str) -> Result<Ident, Error> {
+    if let Some(digits) = text.strip_prefix('#') {
+        let n = digits.parse::<u64>().or(Err("invalid anonymous ident"))?;
+        Ok(Ident::Anonymous(n))
+    } else if text.is_empty() {
+        Err("empty name".into())
+    } else if text.contains(RESERVED) {
+        Err("reserved character in name".into())
+    } else {
+        Ok(Ident::NonIntern(text.to_string()))
+    }
+}
+
+impl Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            match &step.selector {
+                Selector::Wildcard => f.write_str("*")?,
+                Selector::Name(name) => IdentPath::new(name.clone()).fmt(f)?,
+            }
+            for filter in &step.filters {
+                let Literal { text, quoted } = &filter.value;
+                let key = IdentPath::new(filter.key.clone());
+                if *quoted {
+                    write!(f, "[{key}=\"{text}\"]")?
+                } else {
+                    write!(f, "[{key}={text}]")?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Query {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Query {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::rc::Rc;
+
+    fn service(item: i64, fee: i64) -> Variant {
+        let mut t = Table::new();
+        t.join_entry(Ident::Intern("item"), Variant::Int(item));
+        t.join_entry(Ident::Intern("fee"), Variant::Int(fee));
+        Rc::new(t).into()
+    }
+
+    fn claim() -> Table {
+        let mut services = Table::new();
+        services.join_entry(Ident::Anonymous(0), service(51300, 100));
+        services.join_entry(Ident::Anonymous(1), service(51303, 200));
+        services.join_entry(Ident::Anonymous(2), service(51300, 300));
+        let mut claim = Table::new();
+        claim.join_entry(Ident::Intern("services"), Rc::new(services).into());
+        let mut root = Table::new();
+        root.join_entry(Ident::Intern("claim"), Rc::new(claim).into());
+        root
+    }
+
+    fn matches(query: &str, table: &Table) -> Vec<String> {
+        let query: Query = query.parse().unwrap();
+        let mut found: Vec<String> = query
+            .evaluate(table)
+            .into_iter()
+            .map(|(p, v)| format!("{p}={v}"))
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn query_evaluation() {
+        let table = claim();
+        assert_eq!(
+            matches("claim/services/*/fee", &table),
+            [
+                "claim/services/#0/fee=100",
+                "claim/services/#1/fee=200",
+                "claim/services/#2/fee=300"
+            ]
+        );
+        assert_eq!(
+            matches("claim/services[item=51300]/fee", &table),
+            ["claim/services/#0/fee=100", "claim/services/#2/fee=300"]
+        );
+        assert_eq!(
+            matches("claim/services/#1/item", &table),
+            ["claim/services/#1/item=51303"]
+        );
+        assert!(matches("claim/services[item=\"51300\"]/fee", &table).is_empty());
+        assert!(matches("claim/absent/*", &table).is_empty());
+    }
+
+    #[test]
+    fn query_round_trip() {
+        for text in [
+            "claim/services/*/fee",
+            "claim/services[item=51300]/fee",
+            "a/#3[b=\"x/y\"][c=2024-07-01]/*",
+            "a[#3=1]/#0[#12=\"x\"]",
+        ] {
+            let query: Query = text.parse().unwrap();
+            assert_eq!(query.to_string(), text);
+            let json = serde_json::to_string(&query).unwrap();
+            assert_eq!(serde_json::from_str::<Query>(&json).unwrap(), query);
+        }
+        assert!("a//b".parse::<Query>().is_err());
+        assert!("a[b]".parse::<Query>().is_err());
+        assert!("a[b=1".parse::<Query>().is_err());
+        assert!("a[b=x\"y]".parse::<Query>().is_err());
+        assert!("a[b=\"x\"y\"]".parse::<Query>().is_err());
+    }
+}
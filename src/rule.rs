@@ -1,6 +1,6 @@
 use crate::{
     propagator::Propagator,
-    property::{Path, Property},
+    property::{Path, Property, PropertyType},
     table::{Ident, IdentPath, Table},
     variant::{Error, Variant},
 };
@@ -92,6 +92,7 @@ impl<A, B> Rule<Property<A>, Path<B>, ()> {
 
 impl<A, B, C> Rule<Property<A>, (Path<B>, Path<C>), ()> {
     /// Add the 3rd dependency to a rule.  The dependency is a path of type `D`.
+    #[allow(clippy::type_complexity)]
     pub fn from<D>(
         self,
         path: impl Into<Path<D>>,
@@ -137,11 +138,15 @@ impl<A, B, C, D> Rule<Property<A>, (Path<B>, Path<C>, Path<D>), ()> {
     }
 }
 
+fn typed<A: 'static>(target: &Property<A>) -> (IdentPath, PropertyType) {
+    (IdentPath::new(target.name.clone()), PropertyType::of::<A>())
+}
+
 impl<A, B, F> Propagator for Rule<Property<A>, Path<B>, FuncOptional<F>>
 where
     F: Fn(B) -> Option<A>,
-    A: Into<Variant>,
-    B: TryFrom<Variant>,
+    A: Into<Variant> + 'static,
+    B: TryFrom<Variant> + 'static,
 {
     fn target(&self) -> &Ident {
         &self.output.name
@@ -151,6 +156,13 @@ where
         Vec::from([])
     }
 
+    fn types(&self) -> Vec<(IdentPath, PropertyType)> {
+        Vec::from([
+            typed::<A>(&self.output),
+            (self.input.ident_path().clone(), PropertyType::of::<B>()),
+        ])
+    }
+
     fn fire(&self, state: &Table) -> Option<Variant> {
         Some((self.func.0)(self.input.query(state)?)?.into())
     }
@@ -159,8 +171,8 @@ where
 impl<A, B, F> Propagator for Rule<Property<A>, Path<B>, FuncFallible<F>>
 where
     F: Fn(B) -> Result<Option<A>, Error>,
-    A: Into<Variant>,
-    B: TryFrom<Variant>,
+    A: Into<Variant> + 'static,
+    B: TryFrom<Variant> + 'static,
 {
     fn target(&self) -> &Ident {
         &self.output.name
@@ -170,6 +182,13 @@ where
         Vec::from([self.input.ident_path()])
     }
 
+    fn types(&self) -> Vec<(IdentPath, PropertyType)> {
+        Vec::from([
+            typed::<A>(&self.output),
+            (self.input.ident_path().clone(), PropertyType::of::<B>()),
+        ])
+    }
+
     fn fire(&self, state: &Table) -> Option<Variant> {
         match (self.func.0)(self.input.query(state)?) {
             Ok(Some(x)) => Some(x.into()),
@@ -182,9 +201,9 @@ where
 impl<A, B, C, F> Propagator for Rule<Property<A>, (Path<B>, Path<C>), FuncOptional<F>>
 where
     F: Fn((B, C)) -> Option<A>,
-    A: Into<Variant>,
-    B: TryFrom<Variant>,
-    C: TryFrom<Variant>,
+    A: Into<Variant> + 'static,
+    B: TryFrom<Variant> + 'static,
+    C: TryFrom<Variant> + 'static,
 {
     fn target(&self) -> &Ident {
         &self.output.name
@@ -194,6 +213,14 @@ where
         Vec::from([self.input.0.ident_path(), self.input.1.ident_path()])
     }
 
+    fn types(&self) -> Vec<(IdentPath, PropertyType)> {
+        Vec::from([
+            typed::<A>(&self.output),
+            (self.input.0.ident_path().clone(), PropertyType::of::<B>()),
+            (self.input.1.ident_path().clone(), PropertyType::of::<C>()),
+        ])
+    }
+
     fn fire(&self, state: &Table) -> Option<Variant> {
         Some((self.func.0)((self.input.0.query(state)?, self.input.1.query(state)?))?.into())
     }
@@ -202,10 +229,10 @@ where
 impl<A, B, C, D, F> Propagator for Rule<Property<A>, (Path<B>, Path<C>, Path<D>), FuncOptional<F>>
 where
     F: Fn((B, C, D)) -> Option<A>,
-    A: Into<Variant>,
-    B: TryFrom<Variant>,
-    C: TryFrom<Variant>,
-    D: TryFrom<Variant>,
+    A: Into<Variant> + 'static,
+    B: TryFrom<Variant> + 'static,
+    C: TryFrom<Variant> + 'static,
+    D: TryFrom<Variant> + 'static,
 {
     fn target(&self) -> &Ident {
         &self.output.name
@@ -219,6 +246,15 @@ where
         ])
     }
 
+    fn types(&self) -> Vec<(IdentPath, PropertyType)> {
+        Vec::from([
+            typed::<A>(&self.output),
+            (self.input.0.ident_path().clone(), PropertyType::of::<B>()),
+            (self.input.1.ident_path().clone(), PropertyType::of::<C>()),
+            (self.input.2.ident_path().clone(), PropertyType::of::<D>()),
+        ])
+    }
+
     fn fire(&self, state: &Table) -> Option<Variant> {
         Some(
             (self.func.0)((
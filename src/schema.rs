@@ -0,0 +1,530 @@
+use crate::{
+    propagator::Propagators,
+    property::{Property, PropertyType},
+    table::{Element, Ident, IdentPath, Set, Table},
+    variant::{Error, Variant},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::{collections::HashMap, fmt::Debug, marker::PhantomData, rc::Rc};
+
+#[cfg(feature = "quantity")]
+use crate::quantity::{Quantity, Value};
+
+/// The kind of value a property holds, which determines how it is read from and written to text.
+///
+/// The scalar kinds are written in a plain form: dates in ISO 8601 form and sets as comma separated names.
+/// A quantity kind, constructed by `Kind::quantity`, is written in the form of the quantity
+/// e.g. `$12.34` for `AUD`.
+#[derive(Clone)]
+pub enum Kind {
+    String,
+    Int,
+    Float,
+    Date,
+    Instant,
+    Set,
+    Table(Rc<Schema>),
+    Quantity(QuantityKind),
+}
+
+/// The conversions for a `Kind::Quantity`.
+#[derive(Clone, Copy)]
+pub struct QuantityKind {
+    /// The name of the quantity type e.g. `AUD`
+    pub name: &'static str,
+    parse: fn(&str) -> Result<Variant, Error>,
+    format: fn(&Variant) -> Result<String, Error>,
+}
+
+impl Debug for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::String => f.write_str("String"),
+            Kind::Int => f.write_str("Int"),
+            Kind::Float => f.write_str("Float"),
+            Kind::Date => f.write_str("Date"),
+            Kind::Instant => f.write_str("Instant"),
+            Kind::Set => f.write_str("Set"),
+            Kind::Table(schema) => f.debug_tuple("Table").field(schema).finish(),
+            Kind::Quantity(q) => f.debug_tuple("Quantity").field(&q.name).finish(),
+        }
+    }
+}
+
+impl Kind {
+    /// The kind of the values of quantity `Q`.
+    #[cfg(feature = "quantity")]
+    pub fn quantity<Q>() -> Self
+    where
+        Q: Quantity + 'static,
+        Value<Q>: Into<Variant> + TryFrom<Variant>,
+    {
+        let name = std::any::type_name::<Q>();
+        Kind::Quantity(QuantityKind {
+            name: name.rsplit("::").next().unwrap_or(name),
+            parse: |text| Ok(text.parse::<Value<Q>>()?.into()),
+            format: |value| {
+                let value: Value<Q> = value
+                    .clone()
+                    .try_into()
+                    .or(Err("incorrect type stored in variant"))?;
+                Ok(value.to_string())
+            },
+        })
+    }
+
+    /// The kind of a value in its natural form, if it is not a `Conflict`, `Custom` or `Invalid`.
+    pub fn of(value: &Variant) -> Option<Self> {
+        Some(match value {
+            Variant::String(_) => Kind::String,
+            Variant::Date(_) => Kind::Date,
+            Variant::Instant(_) => Kind::Instant,
+            Variant::Float(_) => Kind::Float,
+            Variant::Int(_) => Kind::Int,
+            Variant::Set(_) => Kind::Set,
+            Variant::Table(_) => Kind::Table(Rc::default()),
+            Variant::Conflict(_, _) | Variant::Custom(_) | Variant::Invalid(_) => None?,
+        })
+    }
+
+    /// Read a value of this kind.
+    pub fn parse(&self, text: &str) -> Result<Variant, Error> {
+        let error = || Error::Detail(format!("expected {self:?} value, got '{text}'"));
+        let trimmed = text.trim();
+        Ok(match self {
+            Kind::String => Variant::String(text.to_string()),
+            Kind::Int => Variant::Int(trimmed.parse().or(Err(error()))?),
+            Kind::Float => Variant::Float(trimmed.parse().or(Err(error()))?),
+            Kind::Date => Variant::Date(NaiveDate::parse_from_str(trimmed, "%F").or(Err(error()))?),
+            Kind::Instant => Variant::Instant(
+                DateTime::parse_from_rfc3339(trimmed)
+                    .or(Err(error()))?
+                    .to_utc(),
+            ),
+            Kind::Set => Variant::Set(Set::new(
+                trimmed
+                    .split(',')
+                    .filter(|s| !s.trim().is_empty())
+                    .map(str::parse::<Element>)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            Kind::Table(_) => Err("a table cannot be read from text")?,
+            Kind::Quantity(q) => (q.parse)(trimmed)?,
+        })
+    }
+
+    /// Write a value of this kind.
+    pub fn format(&self, value: &Variant) -> Result<String, Error> {
+        let error = || Error::Detail(format!("expected {self:?} value, got {value}"));
+        Ok(match (self, value) {
+            (Kind::String, Variant::String(s)) => s.clone(),
+            (Kind::Int, Variant::Int(i)) => i.to_string(),
+            (Kind::Float, Variant::Float(x)) => x.to_string(),
+            (Kind::Float, Variant::Int(i)) => i.to_string(),
+            (Kind::Date, Variant::Date(d)) => d.format("%F").to_string(),
+            (Kind::Instant, Variant::Instant(t)) => t.to_rfc3339(),
+            (Kind::Set, Variant::Set(s)) => {
+                let mut elems: Vec<_> = s.iter().collect();
+                elems.sort();
+                let elems: Vec<_> = elems.iter().map(|e| e.to_string()).collect();
+                elems.join(",")
+            }
+            (Kind::Quantity(q), value) => (q.format)(value).map_err(|_| error())?,
+            _ => Err(error())?,
+        })
+    }
+}
+
+/// The type of a property's values, recovered at runtime as a `Kind`.
+pub trait HasKind {
+    fn kind() -> Kind;
+}
+
+macro_rules! has_kind {
+    ($($t:ty => $kind:ident),*) => {$(
+        impl HasKind for $t {
+            fn kind() -> Kind {
+                Kind::$kind
+            }
+        }
+    )*};
+}
+
+has_kind!(String => String, i64 => Int, i32 => Int, u32 => Int, f64 => Float,
+    NaiveDate => Date, DateTime<Utc> => Instant, Set => Set);
+
+impl HasKind for Rc<Table> {
+    fn kind() -> Kind {
+        Kind::Table(Rc::default())
+    }
+}
+
+#[cfg(feature = "quantity")]
+impl<Q> HasKind for Value<Q>
+where
+    Q: Quantity + 'static,
+    Value<Q>: Into<Variant> + TryFrom<Variant>,
+{
+    fn kind() -> Kind {
+        Kind::quantity::<Q>()
+    }
+}
+
+/// The declaration of a property in a `Schema`: its kind, a description and its allowed values.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub kind: Kind,
+    pub description: Option<String>,
+    pub allowed: Vec<Variant>,
+    ty: Option<PropertyType>,
+    accepts: fn(&Variant) -> bool,
+}
+
+impl Entry {
+    fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            description: None,
+            allowed: Vec::new(),
+            ty: None,
+            accepts: |_| true,
+        }
+    }
+
+    /// The type of the property static it was declared with, if any.
+    pub fn ty(&self) -> Option<&PropertyType> {
+        self.ty.as_ref()
+    }
+
+    fn check(&self, value: &Variant) -> Result<(), Error> {
+        let text = self.kind.format(value)?;
+        if !(self.accepts)(value) {
+            Err(Error::Detail(format!("{text} is out of range")))?
+        }
+        if !self.allowed.is_empty()
+            && !self
+                .allowed
+                .iter()
+                .any(|a| self.kind.format(a).is_ok_and(|a| a == text))
+        {
+            Err(Error::Detail(format!("{text} is not an allowed value")))?
+        }
+        Ok(())
+    }
+}
+
+/// A declaration of a property under construction. See `describe`.
+pub struct Declaration<A> {
+    name: Ident,
+    entry: Entry,
+    marker: PhantomData<A>,
+}
+
+/// Declare a property with a description, whose kind follows from its type.
+///
+/// For example:
+///
+/// `Schema::new().declare(describe(&ITEM, "MBS item number").allow([51300, 51303]))`
+pub fn describe<A>(prop: &Property<A>, description: &str) -> Declaration<A>
+where
+    A: HasKind + TryFrom<Variant> + 'static,
+{
+    let mut declaration = Declaration::from(prop);
+    declaration.entry.description = Some(description.to_string());
+    declaration
+}
+
+impl<A> Declaration<A> {
+    /// Restrict the property to the given values.
+    pub fn allow(mut self, values: impl IntoIterator<Item = A>) -> Self
+    where
+        A: Into<Variant>,
+    {
+        self.entry
+            .allowed
+            .extend(values.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// The declared type also limits the values, so that a `u32` property cannot be negative.
+impl<A> From<&Property<A>> for Declaration<A>
+where
+    A: HasKind + TryFrom<Variant> + 'static,
+{
+    fn from(prop: &Property<A>) -> Self {
+        Self {
+            name: prop.name.clone(),
+            entry: Entry {
+                ty: Some(PropertyType::of::<A>()),
+                accepts: |value| A::try_from(value.clone()).is_ok(),
+                ..Entry::new(A::kind())
+            },
+            marker: PhantomData,
+        }
+    }
+}
+
+/// A registry of the properties in a table with their kinds, descriptions and allowed values.
+///
+/// A schema is populated from the property statics, whose types give their kinds,
+/// and `check_rules` confirms that it covers the properties of a rule corpus.
+/// It guides the conversion of a table to and from text forms such as JSON
+/// and it validates input tables before evaluation.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    entries: HashMap<Ident, Entry>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a property.
+    pub fn declare<A>(mut self, declaration: impl Into<Declaration<A>>) -> Self {
+        let declaration = declaration.into();
+        self.entries.insert(declaration.name, declaration.entry);
+        self
+    }
+
+    /// Declare a property with a kind other than that of its type, such as a nested table.
+    pub fn with<A>(mut self, prop: &Property<A>, kind: Kind) -> Self {
+        self.entries.insert(prop.name.clone(), Entry::new(kind));
+        self
+    }
+
+    /// Declare a property of a quantity, whose kind follows from its type.
+    #[cfg(feature = "quantity")]
+    pub fn with_quantity<Q>(self, prop: &Property<Value<Q>>) -> Self
+    where
+        Q: Quantity + 'static,
+        Value<Q>: Into<Variant> + TryFrom<Variant>,
+    {
+        self.declare(prop)
+    }
+
+    /// The declaration of a property, if any.
+    pub fn entry(&self, name: &Ident) -> Option<&Entry> {
+        self.entries.get(name)
+    }
+
+    /// Iterate over the declared properties in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&Ident, &Entry)> {
+        self.entries.iter()
+    }
+
+    /// Check that every value in a table is of the declared kind and is allowed.
+    /// A property that is not declared is a violation, unless the schema is empty.
+    /// Nested tables are checked against their own schemas.
+    /// All violations are reported, each identified by its path.
+    pub fn validate(&self, table: &Table) -> Result<(), Vec<Error>> {
+        let mut violations = Vec::new();
+        self.validate_into(table, None, &mut violations);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    fn validate_into(
+        &self,
+        table: &Table,
+        prefix: Option<&IdentPath>,
+        violations: &mut Vec<Error>,
+    ) {
+        if self.entries.is_empty() {
+            return;
+        }
+        for (name, value) in table.iter() {
+            let path = match prefix {
+                Some(prefix) => prefix.clone().append(name.clone()),
+                None => IdentPath::new(name.clone()),
+            };
+            let result = match (self.entries.get(name), value) {
+                (None, _) => Err(Error::from("undeclared property")),
+                (Some(entry), Variant::Table(inner)) => match &entry.kind {
+                    Kind::Table(schema) => {
+                        schema.validate_into(inner, Some(&path), violations);
+                        Ok(())
+                    }
+                    kind => Err(Error::Detail(format!(
+                        "expected {kind:?} value, got a table"
+                    ))),
+                },
+                (Some(entry), value) => entry.check(value),
+            };
+            if let Err(e) = result {
+                violations.push(Error::Detail(format!("{path}: {e}")));
+            }
+        }
+    }
+
+    /// Check that the schema covers a rule corpus.
+    /// Every property used by the rules must be declared, in a nested schema for a nested path,
+    /// and a property declared from a static must have the type the rules use.
+    /// Each discrepancy is reported once.
+    pub fn check_rules(&self, rules: &Propagators) -> Result<(), Vec<Error>> {
+        let mut errors = Vec::new();
+        let mut seen: Vec<(IdentPath, PropertyType)> = Vec::new();
+        for (path, used) in rules.iter().flat_map(|rule| rule.types()) {
+            if seen.iter().any(|(p, t)| *p == path && *t == used) {
+                continue;
+            }
+            match self.entry_path(&path) {
+                None if seen.iter().any(|(p, _)| *p == path) => {}
+                None => errors.push(Error::Detail(format!("{path}: undeclared property"))),
+                Some(entry) => match entry.ty() {
+                    Some(declared) if *declared != used => errors.push(Error::Detail(format!(
+                        "{path} is declared as {declared} but used as {used}"
+                    ))),
+                    _ => {}
+                },
+            }
+            seen.push((path, used));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The kind of a property, if declared.
+    pub fn get(&self, name: &Ident) -> Option<&Kind> {
+        self.entries.get(name).map(|e| &e.kind)
+    }
+
+    /// The kind of a property in a nested table, if declared.
+    pub fn get_path(&self, path: &IdentPath) -> Option<&Kind> {
+        self.entry_path(path).map(|e| &e.kind)
+    }
+
+    /// The declaration of a property in a nested table, if any.
+    pub fn entry_path(&self, path: &IdentPath) -> Option<&Entry> {
+        let mut schema = self;
+        let mut names = path.iter().peekable();
+        while let Some(name) = names.next() {
+            let entry = schema.entry(name)?;
+            if names.peek().is_none() {
+                return Some(entry);
+            }
+            match &entry.kind {
+                Kind::Table(inner) => schema = inner,
+                _ => return None,
+            }
+        }
+        None
+    }
+}
+
+#[cfg(all(test, feature = "quantity"))]
+mod test {
+    use super::*;
+    use crate::{
+        property::prop,
+        quantity::{money::AUD, proportion::Percent, quant},
+        rule::infer,
+    };
+
+    #[test]
+    fn kinds() {
+        let aud = Kind::quantity::<AUD>();
+        assert!(matches!(aud.parse("$12.34").unwrap(), Variant::Int(1234)));
+        assert_eq!(aud.format(&Variant::Int(1234)).unwrap(), "$12.34");
+        assert!(aud.format(&Variant::String("x".into())).is_err());
+        assert_eq!(format!("{aud:?}"), "Quantity(\"AUD\")");
+        assert!(
+            matches!(Kind::quantity::<Percent>().parse("20%").unwrap(), Variant::Float(f) if f == 0.2)
+        );
+        assert!(matches!(
+            Kind::Date.parse("2024-07-01").unwrap(),
+            Variant::Date(_)
+        ));
+        assert!(Kind::Int.parse("x").is_err());
+        assert_eq!(
+            Kind::Set.format(&Kind::Set.parse("b, a").unwrap()).unwrap(),
+            "a,b"
+        );
+
+        static FEE: Property<Value<AUD>> = quant("fee");
+        static NAME: Property<String> = prop("name");
+        let schema = Schema::new().with_quantity(&FEE).declare(&NAME);
+        assert!(matches!(schema.get(&FEE.name), Some(Kind::Quantity(_))));
+        assert!(matches!(schema.get(&NAME.name), Some(Kind::String)));
+        assert!(schema.get(&Ident::Intern("other")).is_none());
+    }
+
+    #[test]
+    fn validation() {
+        static ITEM: Property<u32> = prop("item");
+        static FEE: Property<Value<AUD>> = quant("surgeon_mbs_fee");
+        static CLAIM: Property<Rc<Table>> = prop("claim");
+        static COUNT: Property<u32> = prop("count");
+
+        let claim = Schema::new()
+            .declare(describe(&ITEM, "MBS item number").allow([51300, 51303]))
+            .declare(describe(&FEE, "Surgeon's MBS fee"))
+            .declare(&COUNT);
+        let schema = Schema::new().with(&CLAIM, Kind::Table(Rc::new(claim.clone())));
+        let entry = claim.entry(&ITEM.name).unwrap();
+        assert_eq!(entry.description.as_deref(), Some("MBS item number"));
+
+        let mut inner = Table::new();
+        inner.join_entry(ITEM.name.clone(), 51303.into());
+        inner.join_entry(FEE.name.clone(), Variant::Int(31035));
+        let mut table = Table::new();
+        table.join_entry(CLAIM.name.clone(), Variant::Table(Rc::new(inner.clone())));
+        assert!(schema.validate(&table).is_ok());
+
+        let mut inner = Table::new();
+        inner.join_entry(ITEM.name.clone(), 1.into());
+        inner.join_entry(FEE.name.clone(), Variant::String("$1".into()));
+        inner.join_entry(COUNT.name.clone(), Variant::Int(-1));
+        let mut table = Table::new();
+        table.join_entry(CLAIM.name.clone(), Variant::Table(Rc::new(inner)));
+        table.join_entry(Ident::Intern("iten"), 1.into());
+        let mut violations: Vec<_> = schema
+            .validate(&table)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        violations.sort();
+        assert_eq!(
+            violations,
+            [
+                "claim/count: -1 is out of range",
+                "claim/item: 1 is not an allowed value",
+                "claim/surgeon_mbs_fee: expected Quantity(\"AUD\") value, got $1",
+                "iten: undeclared property",
+            ]
+        );
+
+        static ITEM_NAME: Property<String> = prop("item");
+        static TOTAL: Property<Value<AUD>> = quant("total");
+        let rules: Propagators = vec![
+            infer(&FEE)
+                .from(&ITEM)
+                .rule(|item: u32| Some(Value::from_repr(item as i64))),
+            infer(&TOTAL).from(&FEE).rule(Some),
+            infer(&COUNT)
+                .from(&ITEM_NAME)
+                .rule(|item: String| Some(item.len() as u32)),
+        ];
+        let errors: Vec<_> = claim
+            .check_rules(&rules)
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "total: undeclared property",
+                "item is declared as u32 but used as alloc::string::String"
+            ]
+        );
+    }
+}
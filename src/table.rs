@@ -1,14 +1,24 @@
-use crate::variant::{Lattice, Variant};
-use derive_more::derive::{Display, From};
+use crate::variant::{Error, Lattice, Variant};
+use chrono::NaiveDate;
+use derive_more::derive::{Display, From, TryInto};
+use im_rc::{hashmap::Entry, HashMap};
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::{hash_map::Entry, HashMap, HashSet},
+    cmp::Ordering,
+    collections::HashSet,
     fmt::Display,
+    hash::{Hash, Hasher},
+    str::FromStr,
 };
 
 /// A `Table` is a map of `Ident` to `Variant`.  
 /// `Table` implements `Lattice`.  Joining a table joins values of the same key.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+///
+/// The map is persistent: a clone shares structure with the original and costs O(1).
+/// Updating a clone copies only the nodes on the path to the changed entry.
+/// This makes it cheap to fork a table, for example to explore alternative inputs,
+/// and it is what makes `Rc::make_mut` on a shared `Variant::Table` inexpensive.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Table(HashMap<Ident, Variant>);
 
 impl Table {
@@ -17,6 +27,27 @@ impl Table {
         Self(HashMap::new())
     }
 
+    /// Create an independent copy of this table in O(1).
+    /// The copy shares structure with the original until either is updated.
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// The number of entries
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// True iff the table has no entries
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterate over the entries in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&Ident, &Variant)> {
+        self.0.iter()
+    }
+
     /// Borrow a value
     pub fn get(&self, name: &Ident) -> Option<&Variant> {
         self.0.get(name)
@@ -40,14 +71,48 @@ impl Table {
                 slot.insert(value);
                 true
             }
-            Entry::Occupied(slot) => {
-                let modified = slot.into_mut().join_update(value);
-                modified
-            }
+            Entry::Occupied(slot) => slot.into_mut().join_update(value),
         }
     }
 }
 
+impl Table {
+    // The entries in order of their keys.
+    fn sorted(&self) -> Vec<(&Ident, &Variant)> {
+        let mut entries: Vec<_> = self.0.iter().collect();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        entries
+    }
+}
+
+/// Tables are equal if they have the same keys and equal values.
+impl PartialEq for Table {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().all(|(k, v)| other.get(k) == Some(v))
+    }
+}
+
+impl Eq for Table {}
+
+impl PartialOrd for Table {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Tables are ordered by their entries in order of their keys.
+impl Ord for Table {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorted().cmp(&other.sorted())
+    }
+}
+
+impl Hash for Table {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sorted().hash(state)
+    }
+}
+
 impl Lattice for Table {
     fn join_update(&mut self, other: Self) -> bool {
         let mut modified = false;
@@ -58,13 +123,201 @@ impl Lattice for Table {
     }
 }
 
-/// A set of `Ident`s.  This implements `Lattice` and `join` is by set union.
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Set(HashSet<Ident>);
+/// A set of `Ident`s and scalar values.  This implements `Lattice` and `join` is by set union.
+///
+/// A set is converted to and from a `HashSet` of any element type, so a property
+/// such as `Property<HashSet<u32>>` holds a set of item numbers.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Set(HashSet<Element>);
 
 impl Set {
-    pub fn new(elems: impl IntoIterator<Item = Ident>) -> Self {
-        Self(elems.into_iter().collect())
+    pub fn new(elems: impl IntoIterator<Item = impl Into<Element>>) -> Self {
+        Self(elems.into_iter().map(Into::into).collect())
+    }
+
+    /// Iterate over the elements in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &Element> {
+        self.0.iter()
+    }
+
+    /// True iff the value is an element
+    pub fn contains(&self, elem: impl Into<Element>) -> bool {
+        self.0.contains(&elem.into())
+    }
+
+    /// The number of elements
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// True iff there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // The elements in order.
+    fn sorted(&self) -> Vec<&Element> {
+        let mut elems: Vec<_> = self.0.iter().collect();
+        elems.sort_unstable();
+        elems
+    }
+}
+
+/// An element of a `Set`, which is an `Ident` or a hashable scalar value.
+///
+/// A string element is a named `Ident`, so a set of strings is a set of names.
+/// Elements of different types are unequal, so the integer `1` is not the name `"1"`.
+///
+/// An `Ident` is serialized as it would be outside a set, so a set of names is a list of strings.
+/// Integers and dates are tagged e.g. `{"Int":1}`.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, From, TryInto,
+)]
+#[serde(from = "ExternalElement", into = "ExternalElement")]
+pub enum Element {
+    Ident(Ident),
+    Int(i64),
+    Date(NaiveDate),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ExternalElement {
+    Ident(Ident),
+    Scalar(Scalar),
+}
+
+#[derive(Serialize, Deserialize)]
+enum Scalar {
+    Int(i64),
+    Date(NaiveDate),
+}
+
+impl From<ExternalElement> for Element {
+    fn from(value: ExternalElement) -> Self {
+        match value {
+            ExternalElement::Ident(ident) => Element::Ident(ident),
+            ExternalElement::Scalar(Scalar::Int(i)) => Element::Int(i),
+            ExternalElement::Scalar(Scalar::Date(d)) => Element::Date(d),
+        }
+    }
+}
+
+impl From<Element> for ExternalElement {
+    fn from(value: Element) -> Self {
+        match value {
+            Element::Ident(ident) => ExternalElement::Ident(ident),
+            Element::Int(i) => ExternalElement::Scalar(Scalar::Int(i)),
+            Element::Date(d) => ExternalElement::Scalar(Scalar::Date(d)),
+        }
+    }
+}
+
+impl From<String> for Element {
+    fn from(value: String) -> Self {
+        Element::Ident(Ident::NonIntern(value))
+    }
+}
+
+impl From<u32> for Element {
+    fn from(value: u32) -> Self {
+        Element::Int(value.into())
+    }
+}
+
+impl TryFrom<Element> for String {
+    type Error = Error;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        match value {
+            Element::Ident(ident) => Ok(ident
+                .as_str()
+                .ok_or("expected a named element")?
+                .to_string()),
+            _ => Err("expected a named element")?,
+        }
+    }
+}
+
+impl TryFrom<Element> for u32 {
+    type Error = Error;
+
+    fn try_from(value: Element) -> Result<Self, Self::Error> {
+        let i: i64 = value.try_into().or(Err("expected an integer element"))?;
+        Ok(i.try_into().or(Err("integer element out of range"))?)
+    }
+}
+
+/// An `Int`, `String` or `Date` variant is an element.
+impl TryFrom<Variant> for Element {
+    type Error = Error;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Variant::Int(i) => Element::Int(i),
+            Variant::String(s) => s.into(),
+            Variant::Date(d) => Element::Date(d),
+            other => Err(Error::Detail(format!("{other} cannot be a set element")))?,
+        })
+    }
+}
+
+/// Write an element as an integer, an ISO 8601 date, `#n` for an anonymous ident or a name.
+impl Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Element::Ident(Ident::Anonymous(n)) => write!(f, "#{n}"),
+            Element::Ident(ident) => ident.fmt(f),
+            Element::Int(i) => i.fmt(f),
+            Element::Date(d) => d.fmt(f),
+        }
+    }
+}
+
+/// Read an element, the inverse of `Display`.  Text that reads as an integer or a date is not a name.
+impl FromStr for Element {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        Ok(if let Ok(i) = s.parse() {
+            Element::Int(i)
+        } else if let Ok(d) = NaiveDate::parse_from_str(s, "%F") {
+            Element::Date(d)
+        } else if let Some(n) = s.strip_prefix('#').and_then(|n| n.parse().ok()) {
+            Element::Ident(Ident::Anonymous(n))
+        } else if s.is_empty() {
+            Err("empty set element")?
+        } else {
+            Element::Ident(Ident::NonIntern(s.to_string()))
+        })
+    }
+}
+
+impl PartialEq for Set {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Set {}
+
+impl PartialOrd for Set {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Sets are ordered by their elements in order.
+impl Ord for Set {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sorted().cmp(&other.sorted())
+    }
+}
+
+impl Hash for Set {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sorted().hash(state)
     }
 }
 
@@ -90,13 +343,64 @@ impl Display for Set {
 }
 
 /// An `Ident` identifies a table entry or an element of a set.
-#[derive(PartialEq, Eq, Hash, Debug, Display, From, Clone)]
+///
+/// Named idents are equal if their names are equal, whether or not they are interned.
+#[derive(Debug, Display, From, Clone)]
 pub enum Ident {
     NonIntern(String),
     Intern(&'static str),
     Anonymous(u64),
 }
 
+impl Ident {
+    /// The name of an ident, unless it is anonymous.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Ident::NonIntern(name) => Some(name),
+            Ident::Intern(name) => Some(name),
+            Ident::Anonymous(_) => None,
+        }
+    }
+}
+
+impl PartialEq for Ident {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Ident::Anonymous(a), Ident::Anonymous(b)) => a == b,
+            _ => self.as_str().is_some() && self.as_str() == other.as_str(),
+        }
+    }
+}
+
+impl Eq for Ident {}
+
+impl PartialOrd for Ident {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Named idents are ordered by name, before anonymous idents.
+impl Ord for Ident {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Ident::Anonymous(a), Ident::Anonymous(b)) => a.cmp(b),
+            (Ident::Anonymous(_), _) => Ordering::Greater,
+            (_, Ident::Anonymous(_)) => Ordering::Less,
+            _ => self.as_str().cmp(&other.as_str()),
+        }
+    }
+}
+
+impl Hash for Ident {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Ident::Anonymous(i) => i.hash(state),
+            _ => self.as_str().hash(state),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ExternalIdent {
@@ -134,7 +438,7 @@ impl<'de> Deserialize<'de> for Ident {
 /// An `IdentPath` designates a property that may be in a nested `Table`.
 /// Tables can be nested to any depth because a `Variant` value can be a `Table`.
 /// An `IdentPath` has at least one `Ident`
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IdentPath {
     prefix: Vec<Ident>, // first elements of the path
     subject: Ident,     // the last element of the path
@@ -153,4 +457,126 @@ impl IdentPath {
         prefix.push(self.subject);
         Self { prefix, subject }
     }
+
+    /// Iterate over the `Ident`s of the path from first to last.
+    pub fn iter(&self) -> impl Iterator<Item = &Ident> {
+        self.prefix.iter().chain(std::iter::once(&self.subject))
+    }
+
+    /// The last `Ident` of the path.
+    pub fn subject(&self) -> &Ident {
+        &self.subject
+    }
+}
+
+/// Format a path as `/` separated names in the syntax of a `query::Query`.
+/// Anonymous idents are written `#n`.
+impl Display for IdentPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, ident) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str("/")?;
+            }
+            match ident {
+                Ident::Anonymous(n) => write!(f, "#{n}")?,
+                _ => ident.fmt(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a path written as `/` separated names, the inverse of `Display`.
+impl FromStr for IdentPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut idents = s.split('/').map(|name| match name.trim() {
+            "" => Err(Error::Detail(format!("empty name in path '{s}'"))),
+            name => Ok(match name.strip_prefix('#').and_then(|n| n.parse().ok()) {
+                Some(n) => Ident::Anonymous(n),
+                None => Ident::NonIntern(name.to_string()),
+            }),
+        });
+        let mut path = IdentPath::new(idents.next().ok_or("empty path")??);
+        for ident in idents {
+            path = path.append(ident?);
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn forks_are_independent() {
+        let mut a = Table::new();
+        a.join_entry(Ident::Intern("x"), Variant::Int(1));
+        let mut b = a.fork();
+        b.join_entry(Ident::Intern("y"), Variant::Int(2));
+        assert_eq!(a.len(), 1);
+        assert_eq!(b.len(), 2);
+        assert!(a.get(&Ident::Intern("y")).is_none());
+        assert!(matches!(b.get(&Ident::Intern("x")), Some(Variant::Int(1))));
+    }
+
+    #[test]
+    fn interned_names_match() {
+        let mut a = Table::new();
+        a.join_entry(Ident::NonIntern("x".to_string()), Variant::Int(1));
+        assert!(a.get(&Ident::Intern("x")).is_some());
+        assert_ne!(Ident::Anonymous(1), Ident::NonIntern("1".to_string()));
+    }
+
+    #[test]
+    fn scalar_sets() {
+        use std::collections::HashSet;
+
+        let items: HashSet<u32> = [51300, 51303].into();
+        let mut eligible: Variant = items.clone().into();
+        let dates = Set::new([NaiveDate::from_ymd_opt(2024, 7, 1).unwrap()]);
+        assert!(eligible.join_update(Variant::Set(Set::new([51300u32, 51318]))));
+        let Variant::Set(set) = &eligible else {
+            panic!("expected a set")
+        };
+        assert!(set.contains(51318u32));
+        assert!(!set.contains("51318".to_string()));
+        assert_eq!(set.len(), 3);
+        assert!(dates.contains(NaiveDate::from_ymd_opt(2024, 7, 1).unwrap()));
+
+        let back: HashSet<u32> = eligible.clone().try_into().unwrap();
+        assert!(back.is_superset(&items));
+        assert!(HashSet::<String>::try_from(eligible).is_err());
+        let names: HashSet<String> = Variant::Set(Set::new(["a".to_string()]))
+            .try_into()
+            .unwrap();
+        assert!(names.contains("a"));
+
+        for text in ["51300", "2024-07-01", "#3", "surgery"] {
+            assert_eq!(text.parse::<Element>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn set_serde() {
+        let names: Set = serde_json::from_str(r#"["a",3]"#).unwrap();
+        assert_eq!(
+            names,
+            Set::new([Element::from("a".to_string()), Ident::Anonymous(3).into()])
+        );
+        assert_eq!(
+            serde_json::to_string(&Set::new(["a".to_string()])).unwrap(),
+            r#"["a"]"#
+        );
+
+        let mixed = Set::new([
+            Element::Int(1),
+            NaiveDate::from_ymd_opt(2024, 7, 1).unwrap().into(),
+        ]);
+        let json = serde_json::to_string(&mixed).unwrap();
+        assert!(json.contains(r#"{"Int":1}"#) && json.contains(r#"{"Date":"2024-07-01"}"#));
+        assert_eq!(serde_json::from_str::<Set>(&json).unwrap(), mixed);
+    }
 }
@@ -1,8 +1,16 @@
-use crate::table::{Set, Table};
+use crate::{
+    custom::CustomValue,
+    table::{Element, Set, Table},
+};
 use chrono::{DateTime, NaiveDate, Utc};
 use derive_more::derive::{Display, From, TryInto};
 use serde::{Deserialize, Serialize};
-use std::rc::Rc;
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    hash::{Hash, Hasher},
+    rc::Rc,
+};
 
 /// A general value used in a `Propagator` and also
 /// monomorphic version of the types used in rules.
@@ -14,7 +22,15 @@ use std::rc::Rc;
 /// - `Table` variants are joined by joining their values by key.
 /// - `Schedule` variants are immutable and are joined if equal.
 /// - Scalar variants are joined if equal.
+/// - `Custom` variants of the same type are joined as the type defines.
 /// - Other pairs result in a `Conflict` which is the top of the join lattice.   
+///
+/// `Variant` also implements structural equality, a total order and hashing consistent with both:
+/// - Variants of different types are unequal and are ordered by type,
+///   `Invalid` first and `Conflict` last.  Custom variants are ordered by tag, then as their type defines.
+/// - Floats are equal if numerically equal and all NaNs are equal.  NaN is ordered after infinity.
+/// - Conflicts are equal if they contain the same pair of values in either order.
+/// - Tables and sets are equal if they have equal members and are ordered by their sorted members.
 #[derive(Serialize, Deserialize, Clone, Debug, From, TryInto, Display)]
 pub enum Variant {
     /// Top of the join lattice
@@ -35,6 +51,10 @@ pub enum Variant {
     #[display("Table")]
     Table(Rc<Table>),
 
+    /// A user-defined value, joined as its type defines. See `custom::Custom`.
+    #[serde(with = "crate::custom")]
+    Custom(Rc<dyn CustomValue>),
+
     /// A correctable error, below the above
     Invalid(Error),
 }
@@ -81,8 +101,9 @@ impl Lattice for Variant {
             (String(a), String(b)) if *a == b => false,
             (Date(a), Date(b)) if *a == b => false,
             (Instant(a), Instant(b)) if *a == b => false,
-            (Float(a), Float(b)) if *a == b => false,
+            (Float(a), Float(b)) if float_cmp(*a, b).is_eq() => false,
             (Int(a), Int(b)) if *a == b => false,
+            (a @ Custom(_), Custom(b)) => join_update_custom(a, b),
             (Conflict(_, _), _) => false,
             (a, b @ Conflict(_, _)) => {
                 *a = b;
@@ -102,6 +123,119 @@ impl Lattice for Variant {
     }
 }
 
+impl Variant {
+    // The position of each type in the order of variants.
+    fn rank(&self) -> u8 {
+        match self {
+            Variant::Invalid(_) => 0,
+            Variant::Int(_) => 1,
+            Variant::Float(_) => 2,
+            Variant::String(_) => 3,
+            Variant::Date(_) => 4,
+            Variant::Instant(_) => 5,
+            Variant::Set(_) => 6,
+            Variant::Table(_) => 7,
+            Variant::Custom(_) => 8,
+            Variant::Conflict(_, _) => 9,
+        }
+    }
+}
+
+// Replace NaNs with a single NaN and -0 with 0 so that `total_cmp` agrees with numeric equality.
+fn canonical(x: f64) -> f64 {
+    if x.is_nan() {
+        f64::NAN
+    } else if x == 0.0 {
+        0.0
+    } else {
+        x
+    }
+}
+
+fn float_cmp(a: f64, b: f64) -> Ordering {
+    canonical(a).total_cmp(&canonical(b))
+}
+
+// The members of a conflict in order, so that the order of joining does not matter.
+fn sorted_pair<'a>(a: &'a Variant, b: &'a Variant) -> (&'a Variant, &'a Variant) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Variant {}
+
+impl PartialOrd for Variant {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Variant {
+    fn cmp(&self, other: &Self) -> Ordering {
+        use Variant::*;
+        match (self, other) {
+            (Conflict(a, b), Conflict(c, d)) => sorted_pair(a, b).cmp(&sorted_pair(c, d)),
+            (String(a), String(b)) => a.cmp(b),
+            (Date(a), Date(b)) => a.cmp(b),
+            (Instant(a), Instant(b)) => a.cmp(b),
+            (Float(a), Float(b)) => float_cmp(*a, *b),
+            (Int(a), Int(b)) => a.cmp(b),
+            (Set(a), Set(b)) => a.cmp(b),
+            (Table(a), Table(b)) if Rc::ptr_eq(a, b) => Ordering::Equal,
+            (Table(a), Table(b)) => a.cmp(b),
+            (Custom(a), Custom(b)) => a.cmp_custom(b.as_ref()),
+            (Invalid(a), Invalid(b)) => a.cmp(b),
+            (a, b) => a.rank().cmp(&b.rank()),
+        }
+    }
+}
+
+impl Hash for Variant {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        use Variant::*;
+        self.rank().hash(state);
+        match self {
+            Conflict(a, b) => sorted_pair(a, b).hash(state),
+            String(a) => a.hash(state),
+            Date(a) => a.hash(state),
+            Instant(a) => a.hash(state),
+            Float(a) => canonical(*a).to_bits().hash(state),
+            Int(a) => a.hash(state),
+            Set(a) => a.hash(state),
+            Table(a) => a.hash(state),
+            Custom(a) => a.hash_custom(state),
+            Invalid(a) => a.hash(state),
+        }
+    }
+}
+
+fn join_update_custom(a: &mut Variant, b: Rc<dyn CustomValue>) -> bool {
+    let Variant::Custom(x) = a else {
+        return false;
+    };
+    match x.join_custom(b.as_ref()) {
+        Some(c) if c.cmp_custom(x.as_ref()).is_eq() => false,
+        Some(c) => {
+            *x = c;
+            true
+        }
+        None => {
+            let a1 = std::mem::replace(a, Variant::Int(0));
+            *a = Variant::Conflict(Box::new(a1), Box::new(Variant::Custom(b)));
+            true
+        }
+    }
+}
+
 fn join_update_tables(a: &mut Rc<Table>, b: Rc<Table>) -> bool {
     if Rc::ptr_eq(a, &b) {
         false
@@ -111,7 +245,9 @@ fn join_update_tables(a: &mut Rc<Table>, b: Rc<Table>) -> bool {
 }
 
 /// A skeleton Error type
-#[derive(Debug, Clone, Display, From, Serialize, Deserialize)]
+#[derive(
+    Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Display, From, Serialize, Deserialize,
+)]
 pub enum Error {
     Detail(String),
 }
@@ -136,7 +272,36 @@ where
     }
 }
 
-static CONV_FAIL: &'static str = "numeric conversion failed";
+static CONV_FAIL: &str = "numeric conversion failed";
+
+impl<T> From<HashSet<T>> for Variant
+where
+    T: Into<Element>,
+{
+    fn from(value: HashSet<T>) -> Self {
+        Variant::Set(Set::new(value))
+    }
+}
+
+impl<T> TryFrom<Variant> for HashSet<T>
+where
+    T: TryFrom<Element> + Eq + Hash,
+{
+    type Error = Error;
+
+    fn try_from(value: Variant) -> Result<Self, Self::Error> {
+        let set: Set = value
+            .try_into()
+            .or(Err("incorrect type stored in variant"))?;
+        set.iter()
+            .map(|elem| {
+                elem.clone()
+                    .try_into()
+                    .or(Err(Error::from("incorrect type of set element")))
+            })
+            .collect()
+    }
+}
 
 impl From<u32> for Variant {
     fn from(value: u32) -> Self {
@@ -180,4 +345,37 @@ mod test {
         let u: Rc<Table> = w.try_into().unwrap();
         assert!(Rc::ptr_eq(&t, &u))
     }
+
+    #[test]
+    fn structural_equality() {
+        use crate::table::Ident;
+        use std::collections::HashSet;
+
+        let nan = Variant::Float(f64::NAN);
+        assert_eq!(nan, Variant::Float(-f64::NAN));
+        assert_eq!(Variant::Float(0.0), Variant::Float(-0.0));
+        assert!(Variant::Float(f64::INFINITY) < nan);
+        assert_ne!(Variant::Int(1), Variant::Float(1.0));
+        assert_eq!(nan.clone().join(nan.clone()), nan);
+
+        let ab = Variant::Int(1).join(Variant::String("a".into()));
+        let ba = Variant::String("a".into()).join(Variant::Int(1));
+        assert_eq!(ab, ba);
+        assert!(Variant::Invalid("x".into()) < Variant::Int(0));
+        assert!(ab > Variant::Table(Rc::new(Table::new())));
+
+        let mut a = Table::new();
+        a.join_entry(Ident::Intern("x"), nan.clone());
+        a.join_entry(Ident::Intern("y"), ab.clone());
+        let mut b = Table::new();
+        b.join_entry(Ident::NonIntern("y".into()), ba);
+        b.join_entry(Ident::NonIntern("x".into()), nan);
+        assert_eq!(a, b);
+
+        let set: HashSet<Variant> = [a, b]
+            .into_iter()
+            .map(|t| Variant::Table(Rc::new(t)))
+            .collect();
+        assert_eq!(set.len(), 1);
+    }
 }